@@ -1,16 +1,20 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use std::{
     fs,
     io::{Write, stdout},
-    sync::Arc,
+    sync::{Arc, Mutex, atomic::AtomicBool},
 };
 
 use checkmatier::{
     Board,
+    board::piece::PieceKind,
     edp::EDP,
-    evaluate::{MaterialEvaluator, PositioningEvaluator, SumEvaluator},
+    evaluate::{
+        Evaluator, KingSafetyEvaluator, MaterialEvaluator, MobilityEvaluator,
+        PawnStructureEvaluator, PiecesEvaluator, PositioningEvaluator, SumEvaluator,
+    },
     r#move::Move,
-    search::{MinimaxSearch, SearchAlgorithm, SearchLimits},
+    search::{Score, SearchConfig, SearchInfo, SearchKind, SearchLimits},
 };
 use tabled::{Table, Tabled};
 
@@ -22,10 +26,14 @@ struct TestResult {
     best_move: String,
     #[tabled(rename = "To avoid")]
     to_avoid: String,
+    #[tabled(rename = "Score")]
+    score: String,
     #[tabled(rename = "Id")]
     id: String,
     #[tabled(rename = "Correct")]
     correct: bool,
+    #[tabled(rename = "Nodes")]
+    nodes: usize,
 }
 
 #[derive(Parser)]
@@ -34,16 +42,52 @@ struct Args {
     edp_file: String,
     #[arg(short, long)]
     show_idx: Option<u32>,
+    #[arg(long, default_value_t = SearchKind::default())]
+    search: SearchKind,
+    #[arg(short, long, default_value_t = 5)]
+    depth: u8,
+    /// Selective search techniques to turn off, to measure their effect
+    #[arg(long, value_delimiter = ',')]
+    disable: Vec<Technique>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Technique {
+    NullMove,
+    Lmr,
+    ReverseFutility,
+    Futility,
+    See,
+    CheckExtensions,
+}
+
+impl Args {
+    fn get_config(&self) -> SearchConfig {
+        let mut config = SearchConfig::default();
+        for technique in &self.disable {
+            match technique {
+                Technique::NullMove => config.null_move_pruning = false,
+                Technique::Lmr => config.late_move_reductions = false,
+                Technique::ReverseFutility => config.reverse_futility_pruning = false,
+                Technique::Futility => config.futility_pruning = false,
+                Technique::See => config.see_pruning = false,
+                Technique::CheckExtensions => config.check_extensions = false,
+            }
+        }
+        config
+    }
 }
 
 const EIGENMAN: &str = "tests/eigenman-rapid-engine-test.txt";
 const WAC: &str = "tests/wac.txt";
+const MATE: &str = "tests/mate.txt";
 
 fn main() {
     let args = Args::parse();
     let path = match args.edp_file.as_str() {
         "eigenman" => EIGENMAN,
         "wac" => WAC,
+        "mate" => MATE,
         _ => panic!("Unknown EDP file: {}", args.edp_file),
     };
     let test_suite = fs::read_to_string(path).expect("Failed to read EDP test suite");
@@ -53,15 +97,16 @@ fn main() {
             .lines()
             .nth(idx as usize)
             .expect("Index out of bounds");
-        debug_test_case(line);
+        debug_test_case(line, &args);
     } else {
-        run_all(test_suite);
+        run_all(test_suite, &args);
     }
 }
 
-fn debug_test_case(edp_str: &str) {
+fn debug_test_case(edp_str: &str, args: &Args) {
     let (board, ops) = EDP::from_str(edp_str);
-    let bm = solve(&board);
+    let (bm, info) = solve(&board, args);
+    let score = info.as_ref().map(|info| info.score);
     println!("----------------------------------------------------");
     println!("{}", board);
     println!("----------------------------------------------------");
@@ -73,40 +118,108 @@ fn debug_test_case(edp_str: &str) {
     );
     println!("Actual best move: {}", ops.bm.unwrap_or("?".to_string()));
     println!("Moves to avoid: {}", ops.am.unwrap_or("?".to_string()));
+    println!("Score: {}", format_score(score));
+    if let Some(dm) = ops.dm {
+        println!("Direct mate: {}", dm);
+    }
+    if let Some(info) = info {
+        println!(
+            "Nodes searched: {} (quiescence: {})",
+            info.nodes, info.qnodes
+        );
+        println!(
+            "First move cutoffs: {}",
+            format_rate(info.first_move_cutoffs, info.beta_cutoffs)
+        );
+        println!(
+            "Transposition table hits: {}",
+            format_rate(info.tt_hits, info.tt_probes)
+        );
+        println!("Selective depth: {}", info.seldepth);
+    }
     println!("----------------------------------------------------");
 }
 
-fn solve(board: &Board) -> Option<Move> {
-    let mut search = MinimaxSearch::new();
+/// Returns the best move found and the info reported for the last iteration, with its score in
+/// centipawns and search statistics.
+fn solve(board: &Board, args: &Args) -> (Option<Move>, Option<SearchInfo>) {
+    let mut search = args.search.create();
+    search.set_config(args.get_config());
     let evaluator = Arc::new(SumEvaluator::new(vec![
         Box::new(MaterialEvaluator::new(10)),
         Box::new(PositioningEvaluator::new(1)),
+        Box::new(PawnStructureEvaluator::new(10)),
+        Box::new(KingSafetyEvaluator::new(10)),
+        Box::new(MobilityEvaluator::new(10)),
+        Box::new(PiecesEvaluator::new(10)),
     ]));
 
     let search_limits = SearchLimits {
-        max_depth: Some(3),
+        max_depth: Some(args.depth),
         ..Default::default()
     };
 
-    search.search_simple(&board, evaluator, search_limits)
+    let info = Arc::new(Mutex::new(None));
+    let info_clone = Arc::clone(&info);
+    let pawn_value = evaluator.piece_value(PieceKind::Pawn);
+    let bm = search.search(
+        board,
+        evaluator,
+        search_limits,
+        Arc::new(AtomicBool::new(false)),
+        Some(Box::new(move |mut info: SearchInfo| {
+            if !info.progress {
+                info.score = info.score.to_centipawns(pawn_value);
+                *info_clone.lock().unwrap() = Some(info);
+            }
+        })),
+    );
+    let info = info.lock().unwrap().take();
+    (bm, info)
 }
 
-fn run_all(test_suite: String) -> () {
+fn run_all(test_suite: String, args: &Args) {
     let mut correct = 0;
+    let mut total_nodes = 0;
+    let mut total_qnodes = 0;
+    let mut total_cutoffs = 0;
+    let mut total_first_move_cutoffs = 0;
+    let mut total_tt_probes = 0;
+    let mut total_tt_hits = 0;
     let mut results = Vec::new();
     let total = test_suite.lines().count();
     let time_start = std::time::Instant::now();
 
-    println!("\nRunning EDP test suite with {} positions", total);
+    println!(
+        "\nRunning EDP test suite with {} positions ({} search, depth {})",
+        total, args.search, args.depth
+    );
+    if !args.disable.is_empty() {
+        println!("Disabled: {:?}", args.disable);
+    }
 
     for (i, line) in test_suite.lines().enumerate() {
         let (board, ops) = EDP::from_str(line);
 
-        let bm = solve(&board);
+        let (bm, info) = solve(&board, args);
+        let score = info.as_ref().map(|info| info.score);
+        let nodes = info.as_ref().map_or(0, |info| info.nodes);
+        if let Some(info) = &info {
+            total_nodes += info.nodes;
+            total_qnodes += info.qnodes;
+            total_cutoffs += info.beta_cutoffs;
+            total_first_move_cutoffs += info.first_move_cutoffs;
+            total_tt_probes += info.tt_probes;
+            total_tt_hits += info.tt_hits;
+        }
 
         let my_bm_algebraic = bm.unwrap().to_short_algebraic_notation(&board);
+        // A direct mate has to be found at the right distance, a best move has to match
+        let is_correct = ops
+            .dm
+            .is_none_or(|dm| score == Some(Score::Mate(dm as i32)))
+            && ops.bm.as_ref().is_none_or(|bm| *bm == my_bm_algebraic);
         let bm_algebraic = ops.bm.unwrap_or("?".to_string());
-        let is_correct = bm_algebraic == my_bm_algebraic;
 
         if is_correct {
             correct += 1;
@@ -116,8 +229,10 @@ fn run_all(test_suite: String) -> () {
             best_found: my_bm_algebraic,
             best_move: bm_algebraic,
             to_avoid: ops.am.unwrap_or("?".to_string()),
+            score: format_score(score),
             id: ops.id.unwrap_or("?".to_string()),
             correct: is_correct,
+            nodes,
         });
 
         print!("\rProcessed {}/{} positions", i + 1, total);
@@ -131,5 +246,38 @@ fn run_all(test_suite: String) -> () {
 
     println!("{}", Table::new(results));
     println!("\nCorrect: {}/{}", correct, total);
+    println!(
+        "Nodes searched: {} (quiescence: {})",
+        total_nodes, total_qnodes
+    );
+    println!(
+        "First move cutoffs: {}",
+        format_rate(total_first_move_cutoffs, total_cutoffs)
+    );
+    println!(
+        "Transposition table hits: {}",
+        format_rate(total_tt_hits, total_tt_probes)
+    );
     println!("Time taken: {:.2?}\n", time_end - time_start);
 }
+
+fn format_score(score: Option<Score>) -> String {
+    match score {
+        Some(Score::Centipawns(value)) => format!("{:+.2}", value as f32 / 100.0),
+        Some(Score::Mate(moves)) => format!("#{}", moves),
+        None => "?".to_string(),
+    }
+}
+
+/// Formats `count` out of `total` as a percentage.
+fn format_rate(count: usize, total: usize) -> String {
+    if total == 0 {
+        return "-".to_string();
+    }
+    format!(
+        "{:.1}% ({}/{})",
+        count as f64 * 100.0 / total as f64,
+        count,
+        total
+    )
+}
@@ -11,6 +11,7 @@ struct Results {
 }
 
 fn perft(depth: u8, res: &mut Results, board: &mut Board) {
+    debug_assert_eq!(board.hash(), board.compute_hash());
     if depth == 0 {
         res.total += 1;
         return;
@@ -0,0 +1,202 @@
+use std::sync::{
+    Arc, Mutex, MutexGuard, OnceLock,
+    atomic::{AtomicBool, Ordering},
+};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use checkmatier::{
+    Board,
+    board::piece::PieceKind,
+    evaluate::Evaluator,
+    r#move::Move,
+    search::{Bound, SearchAlgorithm, SearchInfo, SearchLimits},
+};
+
+/// Runs one search at a time on a background thread, printing its `info` lines and `bestmove`,
+/// and lets the UCI loop stop it or turn a ponder search into a normal one.
+pub struct SearchController {
+    // Kept between `go` commands, so the transposition table persists
+    search: Arc<Mutex<Box<dyn SearchAlgorithm + Send>>>,
+    stop_flag: Arc<AtomicBool>,
+    // Set while the running search is a ponder search
+    ponderhit: Option<Arc<OnceLock<Instant>>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl SearchController {
+    pub fn new(search: Box<dyn SearchAlgorithm + Send>) -> Self {
+        Self {
+            search: Arc::new(Mutex::new(search)),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            ponderhit: None,
+            handle: None,
+        }
+    }
+
+    /// The search, once the running one if any has been stopped. Waiting for it to finish on its
+    /// own would block the UCI loop forever during `go infinite` or `go ponder`.
+    pub fn get_search(&mut self) -> MutexGuard<'_, Box<dyn SearchAlgorithm + Send>> {
+        self.stop();
+        self.search.lock().unwrap()
+    }
+
+    /// Starts searching `board`, stopping the running search first. A ponder search ignores the
+    /// time limits until [`Self::ponderhit`], and holds back its `bestmove` until then or until
+    /// it is stopped, as the protocol requires. An infinite search holds it back until stopped,
+    /// even when it runs out of depths to search.
+    pub fn start(
+        &mut self,
+        board: Board,
+        evaluator: Arc<dyn Evaluator>,
+        mut limits: SearchLimits,
+        ponder: bool,
+        infinite: bool,
+    ) {
+        self.stop();
+        self.stop_flag = Arc::new(AtomicBool::new(false));
+        self.ponderhit = ponder.then(|| Arc::new(OnceLock::new()));
+        limits.ponderhit = self.ponderhit.clone();
+
+        let search = Arc::clone(&self.search);
+        let stop_flag = Arc::clone(&self.stop_flag);
+        let ponderhit = self.ponderhit.clone();
+        let pawn_value = evaluator.piece_value(PieceKind::Pawn);
+
+        self.handle = Some(thread::spawn(move || {
+            let pv = Arc::new(Mutex::new(Vec::new()));
+            let pv_clone = Arc::clone(&pv);
+            let info_callback = Box::new(move |info: SearchInfo| {
+                print_info(&info, pawn_value);
+                if info.multipv == 1 && !info.progress {
+                    *pv_clone.lock().unwrap() = info.pv;
+                }
+            });
+
+            let best_move = search.lock().unwrap().search(
+                &board,
+                evaluator,
+                limits,
+                Arc::clone(&stop_flag),
+                Some(info_callback),
+            );
+
+            let held = || infinite || ponderhit.as_ref().is_some_and(|hit| hit.get().is_none());
+            while held() && !stop_flag.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            print_best_move(best_move, &pv.lock().unwrap());
+        }));
+    }
+
+    /// The opponent played the predicted move, the ponder search goes on as a normal search
+    /// whose time limits start now.
+    pub fn ponderhit(&mut self) {
+        if let Some(ponderhit) = &self.ponderhit {
+            let _ = ponderhit.set(Instant::now());
+        }
+    }
+
+    /// Stops the running search, which sends its `bestmove`.
+    pub fn stop(&mut self) {
+        self.stop_flag.store(true, Ordering::Relaxed);
+        self.wait();
+    }
+
+    fn wait(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.join().expect("Search thread panicked");
+        }
+    }
+}
+
+fn to_uci_line(moves: &[Move]) -> String {
+    moves
+        .iter()
+        .map(|m| m.to_uci_notation())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Prints `info`, its score converted to centipawns from the evaluation units in which a pawn is
+/// worth `pawn_value`.
+fn print_info(info: &SearchInfo, pawn_value: i32) {
+    if info.progress {
+        let currmove = info.currmove.map_or(String::new(), |m| {
+            format!(
+                " currmove {} currmovenumber {}",
+                m.to_uci_notation(),
+                info.currmovenumber
+            )
+        });
+        println!(
+            "info depth {} seldepth {}{} nodes {} nps {} hashfull {} time {}",
+            info.depth,
+            info.seldepth,
+            currmove,
+            info.nodes,
+            info.get_nps(),
+            info.hashfull,
+            info.time.as_millis()
+        );
+        return;
+    }
+    println!(
+        "info depth {} seldepth {} multipv {} score {}{} nodes {} nps {} hashfull {} time {} pv {}",
+        info.depth,
+        info.seldepth,
+        info.multipv,
+        info.score.to_centipawns(pawn_value),
+        match info.bound {
+            Bound::Exact => "",
+            Bound::Lower => " lowerbound",
+            Bound::Upper => " upperbound",
+        },
+        info.nodes,
+        info.get_nps(),
+        info.hashfull,
+        info.time.as_millis(),
+        to_uci_line(&info.pv)
+    );
+}
+
+/// Prints `bestmove`, with the reply expected in the principal variation as the move to ponder
+/// on.
+fn print_best_move(best_move: Option<Move>, pv: &[Move]) {
+    let Some(best_move) = best_move else {
+        println!("bestmove (none)");
+        return;
+    };
+    match pv {
+        [first, reply, ..] if *first == best_move => println!(
+            "bestmove {} ponder {}",
+            best_move.to_uci_notation(),
+            reply.to_uci_notation()
+        ),
+        _ => println!("bestmove {}", best_move.to_uci_notation()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use checkmatier::{evaluate::MaterialEvaluator, search::AlphaBetaSearch};
+
+    #[test]
+    fn test_infinite_search_waits_for_stop() {
+        let mut controller = SearchController::new(Box::new(AlphaBetaSearch::new()));
+        // stalemate, the search itself returns at once
+        let board = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+        controller.start(
+            board,
+            Arc::new(MaterialEvaluator::new(1)),
+            SearchLimits::default(),
+            false,
+            true,
+        );
+        thread::sleep(Duration::from_millis(50));
+        assert!(!controller.handle.as_ref().unwrap().is_finished());
+        controller.stop();
+        assert!(controller.handle.is_none());
+    }
+}
@@ -0,0 +1,420 @@
+use std::io;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use checkmatier::Board;
+use checkmatier::board::piece::Color;
+use checkmatier::evaluate::{
+    Evaluator, KingSafetyEvaluator, MaterialEvaluator, MobilityEvaluator, PawnStructureEvaluator,
+    PiecesEvaluator, PositioningEvaluator, SumEvaluator,
+};
+use checkmatier::r#move::Move;
+use checkmatier::search::{DEFAULT_HASH_SIZE_MB, SearchKind, SearchLimits, TimeControl};
+
+mod controller;
+mod options;
+
+use controller::SearchController;
+use options::{OptionType, UciOption, UciOptions};
+
+const ENGINE_NAME: &str = env!("CARGO_PKG_NAME");
+const AUTHORS: &str = env!("CARGO_PKG_AUTHORS");
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Depth searched by a `go` without any limit.
+const DEFAULT_DEPTH: u8 = 10;
+
+const HASH: &str = "Hash";
+const CLEAR_HASH: &str = "Clear Hash";
+const PONDER: &str = "Ponder";
+const THREADS: &str = "Threads";
+const MULTI_PV: &str = "MultiPV";
+const MOVE_OVERHEAD: &str = "Move Overhead";
+const SEARCH: &str = "Search";
+const MATERIAL_WEIGHT: &str = "Material Weight";
+const POSITIONING_WEIGHT: &str = "Positioning Weight";
+const PAWN_STRUCTURE_WEIGHT: &str = "Pawn Structure Weight";
+const KING_SAFETY_WEIGHT: &str = "King Safety Weight";
+const MOBILITY_WEIGHT: &str = "Mobility Weight";
+const PIECES_WEIGHT: &str = "Pieces Weight";
+/// Highest evaluation weight, at which a pawn is worth 1000 and a side with all its pieces
+/// against a bare king still scores far from the mate scores.
+const MAX_WEIGHT: i64 = 10;
+
+fn capitalize(s: &str) -> String {
+    let mut c = s.chars();
+    match c.next() {
+        None => String::new(),
+        Some(f) => f.to_uppercase().collect::<String>() + c.as_str(),
+    }
+}
+
+fn spin(default: i64, min: i64, max: i64) -> OptionType {
+    OptionType::Spin { default, min, max }
+}
+
+fn default_options() -> UciOptions {
+    UciOptions::new(vec![
+        UciOption::new(HASH, spin(DEFAULT_HASH_SIZE_MB as i64, 1, 4096)),
+        UciOption::new(CLEAR_HASH, OptionType::Button),
+        // Only tells the GUI that the engine can ponder, `go ponder` works regardless
+        UciOption::new(PONDER, OptionType::Check { default: false }),
+        UciOption::new(THREADS, spin(1, 1, 64)),
+        UciOption::new(MULTI_PV, spin(1, 1, 256)),
+        // Time kept for communication with the GUI on every move, in milliseconds
+        UciOption::new(MOVE_OVERHEAD, spin(30, 0, 5000)),
+        UciOption::new(
+            SEARCH,
+            OptionType::Combo {
+                default: SearchKind::default().to_string(),
+                vars: SearchKind::ALL
+                    .iter()
+                    .map(|kind| kind.to_string())
+                    .collect(),
+            },
+        ),
+        UciOption::new(MATERIAL_WEIGHT, spin(2, 0, MAX_WEIGHT)),
+        UciOption::new(POSITIONING_WEIGHT, spin(1, 0, MAX_WEIGHT)),
+        UciOption::new(PAWN_STRUCTURE_WEIGHT, spin(2, 0, MAX_WEIGHT)),
+        UciOption::new(KING_SAFETY_WEIGHT, spin(2, 0, MAX_WEIGHT)),
+        UciOption::new(MOBILITY_WEIGHT, spin(2, 0, MAX_WEIGHT)),
+        UciOption::new(PIECES_WEIGHT, spin(2, 0, MAX_WEIGHT)),
+    ])
+}
+
+/// Evaluator weighted by the options. Kept between searches so that its caches stay warm, and
+/// only created again when a weight changes.
+fn create_evaluator(options: &UciOptions) -> Arc<dyn Evaluator> {
+    Arc::new(SumEvaluator::new(vec![
+        Box::new(MaterialEvaluator::new(
+            options.get_spin(MATERIAL_WEIGHT) as i32
+        )),
+        Box::new(PositioningEvaluator::new(
+            options.get_spin(POSITIONING_WEIGHT) as i32,
+        )),
+        Box::new(PawnStructureEvaluator::new(
+            options.get_spin(PAWN_STRUCTURE_WEIGHT) as i32,
+        )),
+        Box::new(KingSafetyEvaluator::new(
+            options.get_spin(KING_SAFETY_WEIGHT) as i32,
+        )),
+        Box::new(MobilityEvaluator::new(
+            options.get_spin(MOBILITY_WEIGHT) as i32
+        )),
+        Box::new(PiecesEvaluator::new(options.get_spin(PIECES_WEIGHT) as i32)),
+    ]))
+}
+
+struct UciEngine {
+    board: Board,
+    // Kept on `ucinewgame`, only changed by `setoption`
+    options: UciOptions,
+    evaluator: Arc<dyn Evaluator>,
+    controller: SearchController,
+}
+
+impl UciEngine {
+    fn new() -> Self {
+        let options = default_options();
+        Self {
+            board: Board::default(),
+            evaluator: create_evaluator(&options),
+            options,
+            controller: SearchController::new(SearchKind::default().create()),
+        }
+    }
+
+    fn handle_new_game(&mut self) {
+        self.board = Board::default();
+        self.controller.get_search().clear();
+    }
+
+    fn print_options(&self) {
+        for option in self.options.iter() {
+            println!("{}", option);
+        }
+    }
+
+    fn get_hash_size_mb(&self) -> usize {
+        self.options.get_spin(HASH) as usize
+    }
+
+    fn get_move_overhead(&self) -> Duration {
+        Duration::from_millis(self.options.get_spin(MOVE_OVERHEAD) as u64)
+    }
+
+    fn handle_setoption(&mut self, parts: &[&str]) {
+        let name_idx = parts.iter().position(|&s| s == "name");
+        let value_idx = parts.iter().position(|&s| s == "value");
+        let Some(name_idx) = name_idx else {
+            eprintln!("Invalid setoption command: {}", parts.join(" "));
+            return;
+        };
+        if value_idx.is_some_and(|value_idx| value_idx < name_idx) {
+            eprintln!("Invalid setoption command: {}", parts.join(" "));
+            return;
+        }
+        let name = parts[name_idx + 1..value_idx.unwrap_or(parts.len())].join(" ");
+        let value = value_idx.map(|idx| parts[idx + 1..].join(" "));
+
+        match self.options.set(&name, value.as_deref()) {
+            Ok(HASH) => {
+                let size_mb = self.get_hash_size_mb();
+                self.controller.get_search().set_hash_size(size_mb);
+            }
+            Ok(CLEAR_HASH) => self.controller.get_search().clear(),
+            Ok(THREADS) => {
+                let count = self.options.get_spin(THREADS) as usize;
+                self.controller.get_search().set_threads(count);
+            }
+            Ok(MULTI_PV) => {
+                let count = self.options.get_spin(MULTI_PV) as usize;
+                self.controller.get_search().set_multi_pv(count);
+            }
+            Ok(SEARCH) => {
+                let kind: SearchKind = self.options.get_combo(SEARCH).parse().unwrap();
+                let mut search = kind.create();
+                search.set_hash_size(self.get_hash_size_mb());
+                search.set_multi_pv(self.options.get_spin(MULTI_PV) as usize);
+                search.set_threads(self.options.get_spin(THREADS) as usize);
+                *self.controller.get_search() = search;
+            }
+            Ok(
+                MATERIAL_WEIGHT
+                | POSITIONING_WEIGHT
+                | PAWN_STRUCTURE_WEIGHT
+                | KING_SAFETY_WEIGHT
+                | MOBILITY_WEIGHT
+                | PIECES_WEIGHT,
+            ) => self.evaluator = create_evaluator(&self.options),
+            // Read when needed
+            Ok(_) => {}
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+
+    fn handle_position(&mut self, parts: &[&str]) {
+        if parts.len() > 1 && parts[1] == "startpos" {
+            self.board = Board::default();
+        } else if parts.len() > 2 && parts[1] == "fen" {
+            let fen_end = parts
+                .iter()
+                .position(|&s| s == "moves")
+                .unwrap_or(parts.len());
+            let fen = parts[2..fen_end].join(" ");
+            self.board = Board::from_fen(&fen);
+        } else {
+            eprintln!("Invalid position command: {}", parts.join(" "));
+            return;
+        }
+
+        if let Some(moves_idx) = parts.iter().position(|&s| s == "moves") {
+            for move_str in &parts[moves_idx + 1..] {
+                if let Some(mv) = Move::from_uci_notation(&self.board, move_str) {
+                    self.board.apply_move(&mv);
+                } else {
+                    eprintln!("Invalid move: {}", move_str);
+                }
+            }
+        }
+    }
+
+    fn handle_go(&mut self, parts: &[&str]) {
+        let mut limits = SearchLimits::default();
+        let mut is_infinite = false;
+        let mut is_ponder = false;
+        let mut movetime = None;
+        let (mut wtime, mut btime, mut winc, mut binc, mut movestogo) =
+            (None, None, None, None, None);
+        let parse_ms = |s: &str| s.parse::<u64>().ok().map(Duration::from_millis);
+
+        let mut i = 1;
+        while i < parts.len() {
+            match parts[i] {
+                "depth" if i + 1 < parts.len() => {
+                    limits.max_depth = parts[i + 1].parse().ok();
+                    i += 2;
+                }
+                "movetime" if i + 1 < parts.len() => {
+                    movetime = parse_ms(parts[i + 1]);
+                    i += 2;
+                }
+                "nodes" if i + 1 < parts.len() => {
+                    limits.max_nodes = parts[i + 1].parse().ok();
+                    i += 2;
+                }
+                "wtime" if i + 1 < parts.len() => {
+                    wtime = parse_ms(parts[i + 1]);
+                    i += 2;
+                }
+                "btime" if i + 1 < parts.len() => {
+                    btime = parse_ms(parts[i + 1]);
+                    i += 2;
+                }
+                "winc" if i + 1 < parts.len() => {
+                    winc = parse_ms(parts[i + 1]);
+                    i += 2;
+                }
+                "binc" if i + 1 < parts.len() => {
+                    binc = parse_ms(parts[i + 1]);
+                    i += 2;
+                }
+                "movestogo" if i + 1 < parts.len() => {
+                    movestogo = parts[i + 1].parse().ok();
+                    i += 2;
+                }
+                "infinite" => {
+                    is_infinite = true;
+                    i += 1;
+                }
+                "ponder" => {
+                    is_ponder = true;
+                    i += 1;
+                }
+                _ => i += 1,
+            }
+        }
+
+        let (time_left, increment) = match self.board.get_active_color() {
+            Color::White => (wtime, winc),
+            Color::Black => (btime, binc),
+        };
+        if is_infinite {
+            limits.max_depth = None;
+        } else if let Some(movetime) = movetime {
+            // The overhead can't take more than half of a short movetime
+            let max_time = movetime.saturating_sub(self.get_move_overhead());
+            limits.max_time = Some(max_time.max(movetime / 2));
+        } else if let Some(time_left) = time_left {
+            let clock = TimeControl {
+                time_left,
+                increment: increment.unwrap_or_default(),
+                moves_to_go: movestogo,
+            };
+            let clock_limits = clock.get_limits(self.get_move_overhead());
+            limits.max_time = clock_limits.max_time;
+            limits.soft_time = clock_limits.soft_time;
+        } else if limits.max_depth.is_none() && limits.max_nodes.is_none() {
+            limits.max_depth = Some(DEFAULT_DEPTH);
+        }
+
+        eprintln!("Limits {:?}", limits);
+        self.controller.start(
+            self.board.clone(),
+            Arc::clone(&self.evaluator),
+            limits,
+            is_ponder,
+            is_infinite,
+        );
+    }
+
+    fn handle_stop(&mut self) {
+        self.controller.stop();
+    }
+
+    fn handle_ponderhit(&mut self) {
+        self.controller.ponderhit();
+    }
+}
+
+/**
+Based on https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html
+*/
+fn main() {
+    println!("{} {} made by Mati", capitalize(ENGINE_NAME), VERSION);
+
+    let mut engine = UciEngine::new();
+    let mut input = String::new();
+
+    loop {
+        eprint!("> ");
+        io::stderr().flush().unwrap();
+        input.clear();
+
+        std::io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read line");
+
+        let command = input.trim();
+        let parts: Vec<&str> = command.split_whitespace().collect();
+
+        if parts.is_empty() {
+            continue;
+        }
+
+        match parts[0] {
+            "quit" => {
+                engine.handle_stop();
+                break;
+            }
+            "uci" => {
+                println!("id name {} {}", capitalize(ENGINE_NAME), VERSION);
+                println!("id author {}", AUTHORS);
+                engine.print_options();
+                println!("uciok");
+            }
+            "debug" => {
+                if parts.len() > 1 {
+                    match parts[1] {
+                        "on" => eprintln!("Debug mode enabled"),
+                        "off" => eprintln!("Debug mode disabled"),
+                        _ => {}
+                    }
+                }
+            }
+            "isready" => println!("readyok"),
+            "setoption" => engine.handle_setoption(&parts),
+            "ucinewgame" => engine.handle_new_game(),
+            "position" => engine.handle_position(&parts),
+            "go" => engine.handle_go(&parts),
+            "stop" => engine.handle_stop(),
+            "ponderhit" => engine.handle_ponderhit(),
+            "show" => println!("{}", engine.board),
+            _ => {
+                eprintln!("Unrecognized command: {}", command);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[test]
+    fn test_setoption_with_value_before_name() {
+        let mut engine = UciEngine::new();
+        engine.handle_setoption(&["setoption", "value", "5", "name", "Hash"]);
+        assert_eq!(engine.get_hash_size_mb(), DEFAULT_HASH_SIZE_MB);
+        engine.handle_setoption(&["setoption", "name", "Hash", "value", "5"]);
+        assert_eq!(engine.get_hash_size_mb(), 5);
+    }
+
+    #[test]
+    fn test_evaluator_kept_until_a_weight_changes() {
+        let mut engine = UciEngine::new();
+        let evaluator = Arc::clone(&engine.evaluator);
+        engine.handle_setoption(&["setoption", "name", "Hash", "value", "1"]);
+        engine.handle_new_game();
+        assert!(Arc::ptr_eq(&evaluator, &engine.evaluator));
+        engine.handle_setoption(&["setoption", "name", "Mobility", "Weight", "value", "5"]);
+        assert!(!Arc::ptr_eq(&evaluator, &engine.evaluator));
+    }
+
+    #[test]
+    fn test_setoption_during_infinite_search() {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut engine = UciEngine::new();
+            engine.handle_go(&["go", "infinite"]);
+            thread::sleep(Duration::from_millis(50));
+            engine.handle_setoption(&["setoption", "name", "Hash", "value", "1"]);
+            engine.handle_new_game();
+            sender.send(engine.get_hash_size_mb()).unwrap();
+        });
+        // the search is stopped instead of waited for
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(10)), Ok(1));
+    }
+}
@@ -0,0 +1,201 @@
+use std::fmt::{Display, Formatter};
+
+/// Type and constraints of an option, as advertised in the `uci` response.
+#[derive(Debug, Clone)]
+pub enum OptionType {
+    Check { default: bool },
+    Spin { default: i64, min: i64, max: i64 },
+    Combo { default: String, vars: Vec<String> },
+    Button,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionValue {
+    Check(bool),
+    Spin(i64),
+    Combo(String),
+    Button,
+}
+
+pub struct UciOption {
+    pub name: &'static str,
+    pub option_type: OptionType,
+    value: OptionValue,
+}
+
+impl UciOption {
+    pub fn new(name: &'static str, option_type: OptionType) -> Self {
+        let value = match &option_type {
+            OptionType::Check { default } => OptionValue::Check(*default),
+            OptionType::Spin { default, .. } => OptionValue::Spin(*default),
+            OptionType::Combo { default, .. } => OptionValue::Combo(default.clone()),
+            OptionType::Button => OptionValue::Button,
+        };
+        Self {
+            name,
+            option_type,
+            value,
+        }
+    }
+
+    /// Parses `value` for this option. Spin values out of range are clamped, combo values are
+    /// matched case-insensitively.
+    fn parse(&self, value: &str) -> Result<OptionValue, String> {
+        let invalid = || format!("Invalid {} value: {}", self.name, value);
+        match &self.option_type {
+            OptionType::Check { .. } => match value.to_lowercase().as_str() {
+                "true" => Ok(OptionValue::Check(true)),
+                "false" => Ok(OptionValue::Check(false)),
+                _ => Err(invalid()),
+            },
+            OptionType::Spin { min, max, .. } => value
+                .parse::<i64>()
+                .map(|v| OptionValue::Spin(v.clamp(*min, *max)))
+                .map_err(|_| invalid()),
+            OptionType::Combo { vars, .. } => vars
+                .iter()
+                .find(|var| var.eq_ignore_ascii_case(value))
+                .map(|var| OptionValue::Combo(var.clone()))
+                .ok_or_else(invalid),
+            OptionType::Button => Ok(OptionValue::Button),
+        }
+    }
+}
+
+/// Formats the option as a line of the `uci` response.
+impl Display for UciOption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "option name {} type ", self.name)?;
+        match &self.option_type {
+            OptionType::Check { default } => write!(f, "check default {}", default),
+            OptionType::Spin { default, min, max } => {
+                write!(f, "spin default {} min {} max {}", default, min, max)
+            }
+            OptionType::Combo { default, vars } => {
+                write!(f, "combo default {}", default)?;
+                for var in vars {
+                    write!(f, " var {}", var)?;
+                }
+                Ok(())
+            }
+            OptionType::Button => write!(f, "button"),
+        }
+    }
+}
+
+/// Options of the engine with their current values, looked up by name case-insensitively as
+/// the protocol requires.
+pub struct UciOptions {
+    options: Vec<UciOption>,
+}
+
+impl UciOptions {
+    pub fn new(options: Vec<UciOption>) -> Self {
+        Self { options }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &UciOption> {
+        self.options.iter()
+    }
+
+    fn find(&self, name: &str) -> Option<&UciOption> {
+        self.options
+            .iter()
+            .find(|option| option.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Sets an option from a `setoption` command, returning its name as registered so the
+    /// caller can apply the change. Buttons don't take a value.
+    pub fn set(&mut self, name: &str, value: Option<&str>) -> Result<&'static str, String> {
+        let option = self
+            .options
+            .iter_mut()
+            .find(|option| option.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Unknown option: {}", name))?;
+        let value = match (&option.option_type, value) {
+            (OptionType::Button, _) => OptionValue::Button,
+            (_, Some(value)) => option.parse(value)?,
+            (_, None) => return Err(format!("Missing value for option {}", option.name)),
+        };
+        option.value = value;
+        Ok(option.name)
+    }
+
+    pub fn get_spin(&self, name: &str) -> i64 {
+        match self.find(name).map(|option| &option.value) {
+            Some(OptionValue::Spin(value)) => *value,
+            _ => panic!("No spin option named {}", name),
+        }
+    }
+
+    pub fn get_combo(&self, name: &str) -> &str {
+        match self.find(name).map(|option| &option.value) {
+            Some(OptionValue::Combo(value)) => value,
+            _ => panic!("No combo option named {}", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> UciOptions {
+        UciOptions::new(vec![
+            UciOption::new(
+                "Hash",
+                OptionType::Spin {
+                    default: 16,
+                    min: 1,
+                    max: 1024,
+                },
+            ),
+            UciOption::new("Ponder", OptionType::Check { default: false }),
+            UciOption::new(
+                "Style",
+                OptionType::Combo {
+                    default: "Normal".to_string(),
+                    vars: vec!["Solid".to_string(), "Normal".to_string()],
+                },
+            ),
+            UciOption::new("Clear Hash", OptionType::Button),
+        ])
+    }
+
+    #[test]
+    fn test_advertised_options() {
+        let lines: Vec<String> = options().iter().map(|o| o.to_string()).collect();
+        assert_eq!(
+            lines,
+            [
+                "option name Hash type spin default 16 min 1 max 1024",
+                "option name Ponder type check default false",
+                "option name Style type combo default Normal var Solid var Normal",
+                "option name Clear Hash type button",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_options() {
+        let mut options = options();
+        assert_eq!(options.set("hash", Some("64")), Ok("Hash"));
+        assert_eq!(options.get_spin("Hash"), 64);
+        assert_eq!(options.set("Hash", Some("100000")), Ok("Hash"));
+        assert_eq!(options.get_spin("Hash"), 1024);
+
+        assert_eq!(options.set("Ponder", Some("true")), Ok("Ponder"));
+        assert!(options.set("Ponder", Some("yes")).is_err());
+        options.set("Style", Some("solid")).unwrap();
+        assert_eq!(options.get_combo("Style"), "Solid");
+        assert_eq!(options.set("clear hash", None), Ok("Clear Hash"));
+
+        // invalid values leave the option unchanged
+        assert!(options.set("Hash", Some("many")).is_err());
+        assert!(options.set("Hash", None).is_err());
+        assert!(options.set("Style", Some("Wild")).is_err());
+        assert!(options.set("Threads", Some("4")).is_err());
+        assert_eq!(options.get_spin("Hash"), 1024);
+        assert_eq!(options.get_combo("Style"), "Solid");
+    }
+}
@@ -1,10 +1,15 @@
 use super::app::App;
-use checkmatier::search::{MinimaxSearch, SearchAlgorithm, SearchLimits};
+use checkmatier::search::{SearchInfo, SearchLimits};
 use std::sync::mpsc::Sender;
 use std::sync::{Arc, atomic::AtomicBool};
 use std::thread;
 use std::time::Instant;
 
+/// Number of best moves shown when analysing a position.
+const ANALYSIS_LINES: usize = 3;
+/// Most search threads selectable, the count doubles up to it.
+const MAX_AI_THREADS: usize = 8;
+
 impl App {
     pub fn toggle_ai(&mut self) {
         self.ai_enabled = !self.ai_enabled;
@@ -17,6 +22,18 @@ impl App {
         }
     }
 
+    pub fn cycle_ai_search_kind(&mut self) {
+        self.ai_search_kind = self.ai_search_kind.next();
+    }
+
+    pub fn cycle_ai_threads(&mut self) {
+        self.ai_threads = if self.ai_threads >= MAX_AI_THREADS {
+            1
+        } else {
+            self.ai_threads * 2
+        };
+    }
+
     pub fn increase_ai_depth(&mut self) {
         if self.ai_depth < 10 {
             self.ai_depth += 1;
@@ -30,37 +47,70 @@ impl App {
     }
 
     pub fn make_ai_move(&mut self) {
+        self.start_search(true);
+    }
+
+    /// Searches the current position for its best moves without playing any of them.
+    pub fn analyse_position(&mut self) {
+        self.start_search(false);
+    }
+
+    fn start_search(&mut self, plays_move: bool) {
         // Start AI search in background and return immediately.
-        if self.ai_searching {
+        if self.ai_searching || self.board.game_result().is_over() {
             return;
         }
 
         self.ai_searching = true;
+        self.ai_plays_move = plays_move;
         self.ai_last_start_move_time = Some(Instant::now());
         let tx: Sender<Option<checkmatier::r#move::Move>> = self.ai_move_tx.clone();
+        let info_tx = self.ai_info_tx.clone();
         let board = self.board.clone();
+        self.ai_search_board = self.board.clone();
         let evaluator = Arc::clone(&self.ai_evaluator);
         let depth = self.ai_depth;
+        let mut search = self.ai_search_kind.create();
+        search.set_threads(self.ai_threads);
+        if !plays_move {
+            search.set_multi_pv(ANALYSIS_LINES);
+        }
 
         thread::spawn(move || {
-            let mut search = MinimaxSearch::new();
             let stop_flag = Arc::new(AtomicBool::new(false));
             let limits = SearchLimits {
                 max_depth: Some(depth),
-                max_time: None,
-                max_nodes: None,
+                ..Default::default()
             };
 
-            let mv = search.search(&board, evaluator, limits, stop_flag, None);
+            let info_callback = Box::new(move |info: SearchInfo| {
+                let _ = info_tx.send(info);
+            });
+            let mv = search.search(&board, evaluator, limits, stop_flag, Some(info_callback));
             let _ = tx.send(mv);
         });
     }
 
     /// Called every UI tick to apply any completed AI move.
     pub fn poll_ai_move(&mut self) {
+        while let Ok(info) = self.ai_info_rx.try_recv() {
+            // The best line starts the report of a new iteration
+            if !info.progress {
+                match &mut self.ai_last_lines {
+                    Some((_, lines)) if info.multipv > 1 => lines.push(info.clone()),
+                    _ => {
+                        self.ai_last_lines =
+                            Some((self.ai_search_board.clone(), vec![info.clone()]))
+                    }
+                }
+            }
+            self.ai_last_info = Some(info);
+        }
         if let Ok(opt_mv) = self.ai_move_rx.try_recv() {
             self.ai_searching = false;
-            if let Some(best_move) = opt_mv {
+            if self.ai_plays_move
+                && let Some(best_move) = opt_mv
+            {
                 self.board.apply_move(&best_move);
                 self.move_history.push(best_move);
                 self.active_square = None;
@@ -1,6 +1,10 @@
 use checkmatier::board::{Board, piece, square::Square};
-use checkmatier::evaluate::{MaterialEvaluator, PositioningEvaluator, SumEvaluator};
+use checkmatier::evaluate::{
+    KingSafetyEvaluator, MaterialEvaluator, MobilityEvaluator, PawnStructureEvaluator,
+    PiecesEvaluator, PositioningEvaluator, SumEvaluator,
+};
 use checkmatier::r#move::Move;
+use checkmatier::search::{SearchInfo, SearchKind};
 use ratatui::layout::Rect;
 use std::cell::Cell;
 use std::sync::{
@@ -19,19 +23,32 @@ pub struct App {
     pub ai_enabled: bool,
     pub ai_color: piece::Color,
     pub ai_depth: u8,
+    pub ai_threads: usize,
+    pub ai_search_kind: SearchKind,
     pub ai_evaluator: Arc<SumEvaluator>,
     pub ai_last_start_move_time: Option<Instant>,
     pub ai_last_end_move_time: Option<Instant>,
     // Channel where background AI search threads send their found move (or None)
     pub ai_move_rx: Receiver<Option<Move>>,
     pub ai_move_tx: Sender<Option<Move>>,
+    // Channel where background AI search threads report finished iterations
+    pub ai_info_rx: Receiver<SearchInfo>,
+    pub ai_info_tx: Sender<SearchInfo>,
+    // Lines of the last reported iteration, best first, with the position they were searched from
+    pub ai_last_lines: Option<(Board, Vec<SearchInfo>)>,
+    // Last report of the running or last search, progress reports included
+    pub ai_last_info: Option<SearchInfo>,
+    pub ai_search_board: Board,
     // Whether an AI search is currently running
     pub ai_searching: bool,
+    // Whether the running search plays its move, rather than only analysing the position
+    pub ai_plays_move: bool,
 }
 
 impl Default for App {
     fn default() -> Self {
         let (tx, rx) = mpsc::channel();
+        let (info_tx, info_rx) = mpsc::channel();
 
         Self {
             board: Board::default(),
@@ -43,15 +60,27 @@ impl Default for App {
             ai_enabled: false,
             ai_color: piece::Color::Black,
             ai_depth: 3,
+            ai_threads: 1,
+            ai_search_kind: SearchKind::default(),
             ai_evaluator: Arc::new(SumEvaluator::new(vec![
                 Box::new(MaterialEvaluator::new(10)),
                 Box::new(PositioningEvaluator::new(1)),
+                Box::new(PawnStructureEvaluator::new(10)),
+                Box::new(KingSafetyEvaluator::new(10)),
+                Box::new(MobilityEvaluator::new(10)),
+                Box::new(PiecesEvaluator::new(10)),
             ])),
             ai_last_start_move_time: None,
             ai_last_end_move_time: None,
             ai_move_rx: rx,
             ai_move_tx: tx,
+            ai_info_rx: info_rx,
+            ai_info_tx: info_tx,
+            ai_last_lines: None,
+            ai_last_info: None,
+            ai_search_board: Board::default(),
             ai_searching: false,
+            ai_plays_move: false,
         }
     }
 }
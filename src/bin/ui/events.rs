@@ -1,6 +1,8 @@
 use super::app::App;
 use checkmatier::board::square::Square;
-use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, MouseEvent, MouseEventKind};
+use ratatui::crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, MouseEvent, MouseEventKind,
+};
 use ratatui::layout::Position;
 use std::io;
 use std::time::Duration;
@@ -13,15 +15,13 @@ impl App {
                     self.handle_key_event(key_event)
                 }
                 Event::Mouse(mouse_event)
-                    if mouse_event.kind == MouseEventKind::Up(event::MouseButton::Left) =>
+                    if mouse_event.kind == MouseEventKind::Up(event::MouseButton::Left)
+                        && self
+                            .board_area
+                            .get()
+                            .contains(Position::new(mouse_event.column, mouse_event.row)) =>
                 {
-                    if self
-                        .board_area
-                        .get()
-                        .contains(Position::new(mouse_event.column, mouse_event.row))
-                    {
-                        self.handle_board_click(mouse_event);
-                    }
+                    self.handle_board_click(mouse_event);
                 }
                 _ => {}
             };
@@ -36,9 +36,12 @@ impl App {
             KeyCode::Char('r') => self.restart_game(),
             KeyCode::Char('a') => self.toggle_ai(),
             KeyCode::Char('c') => self.toggle_ai_color(),
+            KeyCode::Char('s') => self.cycle_ai_search_kind(),
+            KeyCode::Char('t') => self.cycle_ai_threads(),
             KeyCode::Char('+') | KeyCode::Char('=') => self.increase_ai_depth(),
             KeyCode::Char('-') => self.decrease_ai_depth(),
             KeyCode::Char('m') => self.make_ai_move(),
+            KeyCode::Char('v') => self.analyse_position(),
             _ => {}
         }
     }
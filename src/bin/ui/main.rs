@@ -1,13 +1,16 @@
+mod ai_controller;
 mod app;
 mod events;
 mod rendering;
-mod ai_controller;
 
 pub use app::App;
 
-use std::io;
 use ratatui::DefaultTerminal;
-use ratatui::crossterm::{ExecutableCommand, event::{DisableMouseCapture, EnableMouseCapture}};
+use ratatui::crossterm::{
+    ExecutableCommand,
+    event::{DisableMouseCapture, EnableMouseCapture},
+};
+use std::io;
 use std::io::stdout;
 
 pub fn main() -> io::Result<()> {
@@ -20,13 +23,13 @@ pub fn main() -> io::Result<()> {
 fn run(terminal: &mut DefaultTerminal) -> io::Result<()> {
     let mut app = App::default();
     stdout().execute(EnableMouseCapture).unwrap();
-    
+
     while !app.exit {
         app.poll_ai_move();
         terminal.draw(|frame| app.draw(frame))?;
         app.handle_events()?;
     }
-    
+
     stdout().execute(DisableMouseCapture).unwrap();
     Ok(())
 }
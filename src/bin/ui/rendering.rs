@@ -1,8 +1,10 @@
 use std::time::Instant;
 
 use super::app::App;
-use checkmatier::board::{piece, square::Square};
-use checkmatier::r#move::get_square_attackers;
+use checkmatier::board::{GameResult, piece, square::Square};
+use checkmatier::evaluate::{Evaluator, MAX_PHASE, TaperedScore, game_phase};
+use checkmatier::r#move::{Move, get_square_attackers};
+use checkmatier::search::{Bound, Score};
 use ratatui::{
     Frame,
     buffer::Buffer,
@@ -16,6 +18,7 @@ use ratatui::{
 pub const WHITE_ACTIVE_COLOR: Color = Color::Rgb(255, 165, 0);
 pub const BLACK_ACTIVE_COLOR: Color = Color::Rgb(0, 0, 205);
 pub const MUTED_COLOR: Color = Color::Rgb(164, 164, 164);
+pub const HANGING_COLOR: Color = Color::Magenta;
 
 impl App {
     pub fn draw(&self, frame: &mut Frame) {
@@ -35,6 +38,9 @@ impl App {
         let active_piece = self.active_square.and_then(|s| self.board.get_piece(s));
 
         match square {
+            _ if dest_move.is_some_and(|m| self.board.see(m) < 0) => {
+                Style::default().fg(HANGING_COLOR)
+            }
             _ if dest_move.is_some() && dest_move.unwrap().capture.is_some() => {
                 Style::default().fg(Color::LightRed)
             }
@@ -57,9 +63,9 @@ impl App {
     fn get_content_for_square(&self, square: Square) -> String {
         let piece = self.board.get_piece(square);
         let mut char = ' ';
-        if piece.is_some() {
-            char = piece.unwrap().to_char();
-        } else if piece.is_none() && Some(square) == self.active_square {
+        if let Some(piece) = piece {
+            char = piece.to_char();
+        } else if Some(square) == self.active_square {
             char = '?';
         } else if self.possible_moves.iter().any(|m| m.to == square) {
             char = '.';
@@ -123,14 +129,15 @@ impl App {
     fn render_game_state(&self, area: Rect, buf: &mut Buffer) {
         let game_state = self.board.get_game_state();
 
-        let check_state_line = if self.board.is_checkmate() {
-            let winner = self.board.get_active_color().opposite();
-            Line::from(format!("CHECKMATE: {} has won!", winner))
-        } else {
-            Line::from(vec![
+        let check_state_line = match self.board.game_result() {
+            GameResult::Checkmate { winner } => {
+                Line::from(format!("CHECKMATE: {} has won!", winner))
+            }
+            GameResult::Draw(reason) => Line::from(format!("DRAW: {}", reason)),
+            GameResult::Ongoing => Line::from(vec![
                 "Is in check: ".into(),
                 format!("{}", self.board.is_in_check()).bold(),
-            ])
+            ]),
         };
 
         let mut lines = vec![
@@ -176,14 +183,33 @@ impl App {
                 "Possible moves: ".into(),
                 format!("{}", self.possible_moves.len()).cyan().bold(),
             ]));
+
+            // Moves of the selected piece losing material by static exchange evaluation
+            let hanging_str = self
+                .possible_moves
+                .iter()
+                .map(|m| (m, self.board.see(m)))
+                .filter(|(_, see)| *see < 0)
+                .map(|(m, see)| {
+                    format!(
+                        "{} ({:+})",
+                        m.to_short_algebraic_notation(&self.board),
+                        see as f64 / 100.0
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(" ");
+            if !hanging_str.is_empty() {
+                lines.push(Line::from(vec![
+                    "Hangs material: ".into(),
+                    hanging_str.fg(HANGING_COLOR).bold(),
+                ]));
+            }
         }
 
         if let Some(active_sq) = self.active_square {
-            let mut mut_board = self.board.clone();
-            let black_attackers =
-                get_square_attackers(&mut mut_board, active_sq, piece::Color::White);
-            let white_attackers =
-                get_square_attackers(&mut mut_board, active_sq, piece::Color::Black);
+            let black_attackers = get_square_attackers(&self.board, active_sq, piece::Color::White);
+            let white_attackers = get_square_attackers(&self.board, active_sq, piece::Color::Black);
 
             if !white_attackers.is_empty() {
                 let attackers_str = white_attackers
@@ -222,13 +248,20 @@ impl App {
     }
 
     fn render_ai_info(&self, area: Rect, buf: &mut Buffer) {
-        let eval_line = self
-            .ai_evaluator
-            .evaluate_breakdown(&self.board)
-            .iter()
-            .map(|(name, score)| format!("{}: {}", name, score))
-            .collect::<Vec<String>>()
-            .join(" | ");
+        // Middlegame/endgame score of each evaluation term, blended by the game phase. Terms
+        // that don't apply to the position are left out to keep the line short.
+        let eval_line = format!(
+            "phase {}/{} | {}",
+            game_phase(&self.board),
+            MAX_PHASE,
+            self.ai_evaluator
+                .evaluate_breakdown(&self.board)
+                .iter()
+                .filter(|(_, score)| *score != TaperedScore::default())
+                .map(|(name, score)| format!("{}: {}/{}", name, score.mg, score.eg))
+                .collect::<Vec<String>>()
+                .join(" | ")
+        );
 
         let duration_option = match (
             self.ai_last_start_move_time,
@@ -245,7 +278,64 @@ impl App {
             "Last move: N/A".to_string()
         };
 
-        let lines = vec![
+        let pawn_value = self.ai_evaluator.piece_value(piece::PieceKind::Pawn);
+        let pv_lines = match &self.ai_last_lines {
+            Some((board, infos)) => infos
+                .iter()
+                .map(|info| {
+                    format!(
+                        "PV {}: {} (depth {}, score {}{})",
+                        info.multipv,
+                        Move::line_to_short_algebraic_notation(board, &info.pv),
+                        info.depth,
+                        match info.bound {
+                            Bound::Exact => "",
+                            Bound::Lower => ">=",
+                            Bound::Upper => "<=",
+                        },
+                        match info.score.to_centipawns(pawn_value) {
+                            Score::Centipawns(value) => value.to_string(),
+                            Score::Mate(moves) => format!("#{}", moves),
+                        }
+                    )
+                })
+                .collect(),
+            None => vec!["PV: N/A".to_string()],
+        };
+
+        let stats_lines = match &self.ai_last_info {
+            Some(info) => {
+                let currmove = match info.currmove {
+                    Some(m) => format!(
+                        " | move {}: {}",
+                        info.currmovenumber,
+                        m.to_short_algebraic_notation(&self.ai_search_board)
+                    ),
+                    None => String::new(),
+                };
+                vec![
+                    format!(
+                        "Depth {}/{} | nodes {} (quiescence {}) | {} nps | hash {:.1}%",
+                        info.depth,
+                        info.seldepth,
+                        info.nodes,
+                        info.qnodes,
+                        info.get_nps(),
+                        info.hashfull as f64 / 10.0
+                    ),
+                    format!(
+                        "TT hits {:.1}% | cutoffs {} ({:.1}% first move){}",
+                        info.get_tt_hit_rate() * 100.0,
+                        info.beta_cutoffs,
+                        info.first_move_cutoffs as f64 * 100.0 / info.beta_cutoffs.max(1) as f64,
+                        currmove
+                    ),
+                ]
+            }
+            None => vec!["Stats: N/A".to_string()],
+        };
+
+        let mut lines = vec![
             Line::from(vec![
                 "AI: ".into(),
                 if self.ai_enabled {
@@ -254,9 +344,13 @@ impl App {
                     "OFF".red().bold()
                 },
                 " (".into(),
-                format!("{}", self.ai_color).cyan().into(),
+                format!("{}", self.ai_color).cyan(),
+                ", ".into(),
+                format!("{}", self.ai_search_kind).cyan(),
                 ", depth: ".into(),
                 format!("{}", self.ai_depth).yellow().bold(),
+                ", threads: ".into(),
+                format!("{}", self.ai_threads).yellow().bold(),
                 ")".into(),
                 if self.ai_searching {
                     " [searching...]".into()
@@ -266,8 +360,23 @@ impl App {
             ]),
             Line::from(eval_line).fg(Color::Cyan),
             Line::from(time_line).fg(Color::Yellow),
-            Line::from("a: toggle | c: color | +/-: depth | m: move").fg(MUTED_COLOR),
         ];
+        lines.extend(
+            stats_lines
+                .into_iter()
+                .map(|stats_line| Line::from(stats_line).fg(MUTED_COLOR)),
+        );
+        lines.extend(
+            pv_lines
+                .into_iter()
+                .map(|pv_line| Line::from(pv_line).fg(Color::Green)),
+        );
+        lines.push(
+            Line::from(
+                "a: toggle | c: color | s: search | t: threads | +/-: depth | m: move | v: analyse",
+            )
+            .fg(MUTED_COLOR),
+        );
 
         Paragraph::new(lines)
             .block(
@@ -285,7 +394,7 @@ impl Widget for &App {
         let [left_area, right_area] = horizontal.areas(area);
         let vertical_left = Layout::vertical([Constraint::Length(48), Constraint::Length(2)]);
         let [top_area, bottom_area] = vertical_left.areas(left_area);
-        let vertical_right = Layout::vertical([Constraint::Fill(1), Constraint::Length(8)]);
+        let vertical_right = Layout::vertical([Constraint::Fill(1), Constraint::Length(13)]);
         let [game_state_area, ai_info_area] = vertical_right.areas(right_area);
 
         let title = Line::from(" Chess ".bold());
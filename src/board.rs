@@ -1,14 +1,26 @@
+pub mod bitboard;
 mod display;
 mod fen;
 pub mod piece;
+mod result;
+mod see;
 pub mod square;
+mod zobrist;
+
+pub use result::{DrawReason, GameResult};
 
 use crate::{
+    attacks::pawn_attacks,
     board::{
+        bitboard::{Bitboard, EMPTY, first_square, square_bitboard, squares},
         piece::{Color, Piece, PieceKind},
         square::Square,
+        zobrist::KEYS,
+    },
+    r#move::{
+        Move, add_moves_from_square, get_pinned_bitboard, get_square_attackers_bitboard,
+        get_square_attackers_with_occupancy,
     },
-    r#move::{Move, get_moves_from_square, get_square_attackers},
 };
 
 pub type SquareArray = [Option<Piece>; 64];
@@ -26,14 +38,19 @@ pub struct GameState {
     pub en_passant_square: Option<Square>,
     pub castling_rights: CastlingRights,
     pub halfmove_clock: u16,
+    /// Zobrist key of the position this state belongs to, used for repetition detection.
+    hash: u64,
 }
 
 #[derive(Debug, Clone)]
 pub struct Board {
-    squares: SquareArray,
+    pieces: [[Bitboard; 6]; 2],
+    occupancy: [Bitboard; 2],
     is_white_turn: bool,
     pub fullmove_number: u16,
     state_history: Vec<GameState>,
+    hash: u64,
+    pawn_hash: u64,
 }
 
 impl Default for Board {
@@ -43,21 +60,124 @@ impl Default for Board {
 }
 
 impl Board {
+    fn empty() -> Board {
+        Board {
+            pieces: [[EMPTY; 6]; 2],
+            occupancy: [EMPTY; 2],
+            is_white_turn: true,
+            fullmove_number: 1,
+            state_history: Vec::new(),
+            hash: 0,
+            pawn_hash: 0,
+        }
+    }
+
     pub fn get_piece(&self, square: Square) -> Option<Piece> {
-        self.squares[square.to_index()]
+        let bitboard = square_bitboard(square);
+        let color = Color::ALL
+            .into_iter()
+            .find(|color| self.occupancy[color.index()] & bitboard != EMPTY)?;
+        let pieces = &self.pieces[color.index()];
+        PieceKind::ALL
+            .into_iter()
+            .find(|kind| pieces[kind.index()] & bitboard != EMPTY)
+            .map(|kind| Piece::new(color, kind))
     }
 
     pub fn set_piece(&mut self, square: Square, piece: Option<Piece>) {
-        self.squares[square.to_index()] = piece
+        let bitboard = square_bitboard(square);
+        if let Some(old_piece) = self.get_piece(square) {
+            let color = old_piece.get_color().index();
+            self.pieces[color][old_piece.get_kind().index()] &= !bitboard;
+            self.occupancy[color] &= !bitboard;
+            self.hash ^= KEYS.piece(old_piece, square);
+            if old_piece.get_kind() == PieceKind::Pawn {
+                self.pawn_hash ^= KEYS.piece(old_piece, square);
+            }
+        }
+        if let Some(new_piece) = piece {
+            let color = new_piece.get_color().index();
+            self.pieces[color][new_piece.get_kind().index()] |= bitboard;
+            self.occupancy[color] |= bitboard;
+            self.hash ^= KEYS.piece(new_piece, square);
+            if new_piece.get_kind() == PieceKind::Pawn {
+                self.pawn_hash ^= KEYS.piece(new_piece, square);
+            }
+        }
+    }
+
+    /// Zobrist key of the position, maintained incrementally by [`Board::apply_move`] and
+    /// [`Board::undo_move`].
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Zobrist key recomputed from scratch. Should always equal [`Board::hash`], which is what
+    /// perft runs in debug builds check.
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = KEYS.side_to_move(self.get_active_color());
+        for color in Color::ALL {
+            for kind in PieceKind::ALL {
+                let piece = Piece::new(color, kind);
+                for square in squares(self.get_piece_bitboard(piece)) {
+                    hash ^= KEYS.piece(piece, square);
+                }
+            }
+        }
+        if let Some(game_state) = self.state_history.last() {
+            hash ^= self.get_game_state_key(game_state);
+        }
+        hash
+    }
+
+    /// Zobrist key of the pawns alone, under which pawn structure evaluations can be cached.
+    /// Maintained incrementally like [`Board::hash`].
+    pub fn pawn_hash(&self) -> u64 {
+        self.pawn_hash
+    }
+
+    /// Pawn key recomputed from scratch, which should always equal [`Board::pawn_hash`].
+    pub fn compute_pawn_hash(&self) -> u64 {
+        let mut hash = 0;
+        for color in Color::ALL {
+            let pawn = Piece::new(color, PieceKind::Pawn);
+            for square in squares(self.get_piece_bitboard(pawn)) {
+                hash ^= KEYS.piece(pawn, square);
+            }
+        }
+        hash
+    }
+
+    pub fn get_piece_bitboard(&self, piece: Piece) -> Bitboard {
+        self.pieces[piece.get_color().index()][piece.get_kind().index()]
+    }
+
+    pub fn get_color_bitboard(&self, color: Color) -> Bitboard {
+        self.occupancy[color.index()]
+    }
+
+    pub fn get_occupancy(&self) -> Bitboard {
+        self.occupancy[0] | self.occupancy[1]
+    }
+
+    /// Whether `color` has pieces other than pawns and its king.
+    pub fn has_non_pawn_material(&self, color: Color) -> bool {
+        let pawns_and_king = self.get_piece_bitboard(Piece::new(color, PieceKind::Pawn))
+            | self.get_piece_bitboard(Piece::new(color, PieceKind::King));
+        self.get_color_bitboard(color) & !pawns_and_king != EMPTY
+    }
+
+    pub fn get_king_square(&self, color: Color) -> Option<Square> {
+        first_square(self.get_piece_bitboard(Piece::new(color, PieceKind::King)))
     }
 
     pub fn is_square_empty(&self, square: Square) -> bool {
-        self.get_piece(square).is_none()
+        self.get_occupancy() & square_bitboard(square) == EMPTY
     }
 
-    pub fn is_square_attacked(&self, square: Square, by_color: Color) -> bool {
-        let attackers = get_square_attackers(self, square, by_color);
-        !attackers.is_empty()
+    /// Whether any piece of the opposite color of `attacked` attacks the square.
+    pub fn is_square_attacked(&self, square: Square, attacked: Color) -> bool {
+        get_square_attackers_bitboard(self, square, attacked) != EMPTY
     }
 
     pub fn get_active_color(&self) -> Color {
@@ -69,17 +189,42 @@ impl Board {
     }
 
     pub fn get_game_state(&self) -> &GameState {
-        return self.state_history.last().unwrap();
+        self.state_history.last().unwrap()
     }
 
     pub fn get_legal_moves_for_color(&self, color: Color) -> Vec<Move> {
         let all = self.get_moves_for_color(color);
-        let mut legal_moves = Vec::new();
-        let mut mock_board = self.clone();
+        let in_check = self.is_color_in_check(color);
+        let pinned = get_pinned_bitboard(self, color);
+        let mut legal_moves = Vec::with_capacity(all.len());
+        let king_bitboard = self.get_piece_bitboard(Piece::new(color, PieceKind::King));
+        let occupancy_without_king = self.get_occupancy() & !king_bitboard;
+        let mut mock_board: Option<Board> = None;
         for m in all {
+            if m.piece.get_kind() == PieceKind::King {
+                // castling moves are only generated when the king passes through safe squares
+                if m.castling_rook_from_to.is_some()
+                    || get_square_attackers_with_occupancy(
+                        self,
+                        m.to,
+                        color,
+                        occupancy_without_king,
+                    ) == EMPTY
+                {
+                    legal_moves.push(m);
+                }
+                continue;
+            }
+            // Outside of check only en passant and moves of pinned pieces can expose the king,
+            // everything else is legal without playing it out.
+            let is_en_passant_capture = m.capture.is_some() && m.en_passant_square.is_some();
+            if !in_check && !is_en_passant_capture && pinned & square_bitboard(m.from) == EMPTY {
+                legal_moves.push(m);
+                continue;
+            }
+            let mock_board = mock_board.get_or_insert_with(|| self.clone());
             mock_board.apply_move(&m);
-            // apply_move toggles active color, we toggle it again checking for opponent check
-            if !mock_board.is_opponent_in_check() {
+            if !mock_board.is_color_in_check(color) {
                 legal_moves.push(m);
             }
             mock_board.undo_move(&m);
@@ -93,37 +238,15 @@ impl Board {
 
     fn get_moves_for_color(&self, color: Color) -> Vec<Move> {
         let mut moves: Vec<Move> = Vec::new();
-        for square_idx in 0..64 {
-            let square = Square::from_index(square_idx).unwrap();
-            if let Some(piece) = self.get_piece(square)
-                && piece.get_color() == color
-            {
-                moves.extend(get_moves_from_square(&self, square));
-            }
+        for square in squares(self.get_color_bitboard(color)) {
+            add_moves_from_square(self, square, &mut moves);
         }
         moves
     }
 
-    fn get_checking_pieces(&self, color: Color) -> Vec<(Piece, Square)> {
-        let target_piece = Piece::new(color, PieceKind::King);
-        let king_square_option = (0..64).find_map(|idx| {
-            let square = Square::from_index(idx).unwrap();
-            if self.get_piece(square) == Some(target_piece) {
-                Some(square)
-            } else {
-                None
-            }
-        });
-
-        if let Some(king_square) = king_square_option {
-            get_square_attackers(self, king_square, color)
-        } else {
-            Vec::new() // Can't happen on a valid board
-        }
-    }
-
     pub fn is_color_in_check(&self, color: Color) -> bool {
-        !self.get_checking_pieces(color).is_empty()
+        self.get_king_square(color)
+            .is_some_and(|king_square| self.is_square_attacked(king_square, color))
     }
 
     pub fn is_in_check(&self) -> bool {
@@ -139,18 +262,31 @@ impl Board {
     }
 
     pub fn is_draw(&self) -> bool {
-        self.get_legal_moves().is_empty() && !self.is_in_check()
+        matches!(self.game_result(), GameResult::Draw(_))
     }
 
     fn toggle_active_color(&mut self) {
         self.is_white_turn = !self.is_white_turn;
+        self.hash ^= KEYS.side_to_move(Color::Black);
     }
 
-    pub fn get_move_from_algebraic_notation(&self, notation: &str) -> Option<Move> {
-        self.get_legal_moves()
-            .iter()
-            .find(|m| m.to_long_algebraic_notation() == notation)
-            .copied()
+    /// Key of the castling rights and en passant square of `game_state`, in the current position.
+    fn get_game_state_key(&self, game_state: &GameState) -> u64 {
+        KEYS.castling(&game_state.castling_rights)
+            ^ self.get_en_passant_key(game_state.en_passant_square)
+    }
+
+    /// Key of the en passant square, only counted when a pawn of the side to move attacks it: the
+    /// position is otherwise the same as without it, and has to repeat as such.
+    fn get_en_passant_key(&self, en_passant_square: Option<Square>) -> u64 {
+        let color = self.get_active_color();
+        let pawns = self.get_piece_bitboard(Piece::new(color, PieceKind::Pawn));
+        match en_passant_square {
+            Some(square) if pawn_attacks(square, color.opposite()) & pawns != EMPTY => {
+                KEYS.en_passant(Some(square))
+            }
+            _ => 0,
+        }
     }
 
     fn update_castling_rights(rights: &mut CastlingRights, m: &Move) {
@@ -162,55 +298,78 @@ impl Board {
             rights.white_king_side = false;
             rights.white_queen_side = false;
         }
-        if m.piece == Piece::BLACK_ROOK {
-            match m.from {
-                s if s == Square { file: 0, rank: 7 } => rights.black_queen_side = false,
-                s if s == Square { file: 7, rank: 7 } => rights.black_king_side = false,
+        // Moving a rook away from its corner or capturing it there both lose the right
+        for square in [m.from, m.to] {
+            match square {
+                Square { file: 0, rank: 7 } => rights.black_queen_side = false,
+                Square { file: 7, rank: 7 } => rights.black_king_side = false,
+                Square { file: 0, rank: 0 } => rights.white_queen_side = false,
+                Square { file: 7, rank: 0 } => rights.white_king_side = false,
                 _ => {}
             }
         }
-        if m.piece == Piece::WHITE_ROOK {
-            match m.from {
-                s if s == Square { file: 0, rank: 0 } => rights.white_queen_side = false,
-                s if s == Square { file: 7, rank: 0 } => rights.white_king_side = false,
-                _ => {}
-            }
+    }
+
+    fn toggle_piece(&mut self, square: Square, piece: Piece) {
+        let bitboard = square_bitboard(square);
+        let color = piece.get_color().index();
+        self.pieces[color][piece.get_kind().index()] ^= bitboard;
+        self.occupancy[color] ^= bitboard;
+        self.hash ^= KEYS.piece(piece, square);
+        if piece.get_kind() == PieceKind::Pawn {
+            self.pawn_hash ^= KEYS.piece(piece, square);
+        }
+    }
+
+    /// Moves the pieces involved in `m`. Toggling is its own inverse, so the same call both applies
+    /// and undoes the move.
+    fn toggle_move_pieces(&mut self, m: &Move) {
+        if let Some(captured) = m.capture {
+            let captured_square = match m.en_passant_square {
+                Some(sq) => sq,
+                None => m.to,
+            };
+            self.toggle_piece(captured_square, captured);
+        }
+        self.toggle_piece(m.from, m.piece);
+        self.toggle_piece(m.to, m.promotion.unwrap_or(m.piece));
+
+        if let Some((rook_from, rook_to)) = m.castling_rook_from_to {
+            let rook = Piece::new(m.piece.get_color(), PieceKind::Rook);
+            self.toggle_piece(rook_from, rook);
+            self.toggle_piece(rook_to, rook);
         }
     }
 
     pub fn apply_move(&mut self, m: &Move) {
+        // The key depends on the position it was computed in
+        let prev_state_key = self.get_game_state_key(self.get_game_state());
         if m.piece.get_color() == Color::Black {
             self.fullmove_number += 1;
         }
         self.toggle_active_color();
-        self.set_piece(m.to, m.promotion.or(Some(m.piece)));
-        self.set_piece(m.from, None);
+        self.toggle_move_pieces(m);
 
         let prev_state = self.get_game_state();
         let mut new_game_state = GameState {
             en_passant_square: m.en_passant_square,
             castling_rights: prev_state.castling_rights,
-            halfmove_clock: if m.capture.is_some() {
+            halfmove_clock: if m.capture.is_some() || m.piece.get_kind() == PieceKind::Pawn {
                 0
             } else {
                 prev_state.halfmove_clock + 1
             },
+            hash: 0,
         };
 
         Self::update_castling_rights(&mut new_game_state.castling_rights, m);
 
-        if let Some(sq) = m.en_passant_square
-            && m.capture.is_some()
-        {
-            self.set_piece(sq, None);
+        if m.en_passant_square.is_some() && m.capture.is_some() {
             new_game_state.en_passant_square = None;
         }
 
-        if let Some((rook_from, rook_to)) = m.castling_rook_from_to {
-            self.set_piece(rook_to, self.get_piece(rook_from));
-            self.set_piece(rook_from, None);
-        }
-
+        self.hash ^= prev_state_key ^ self.get_game_state_key(&new_game_state);
+        new_game_state.hash = self.hash;
         self.state_history.push(new_game_state);
     }
 
@@ -219,20 +378,176 @@ impl Board {
             self.fullmove_number -= 1;
         }
         self.toggle_active_color();
-        self.set_piece(m.from, m.promotion.or(Some(m.piece)));
-        if let Some(sq) = m.en_passant_square
-            && m.capture.is_some()
-        {
-            self.set_piece(m.to, None);
-            self.set_piece(sq, m.capture);
-        } else {
-            self.set_piece(m.to, m.capture);
+        self.toggle_move_pieces(m);
+
+        self.state_history.pop();
+        self.hash = self.get_game_state().hash;
+    }
+
+    /// Passes the turn without moving, as done by null-move pruning. The halfmove clock starts
+    /// over, so that positions before the null move don't count as repetitions.
+    pub fn apply_null_move(&mut self) {
+        let prev_state_key = self.get_game_state_key(self.get_game_state());
+        self.toggle_active_color();
+
+        let prev_state = self.get_game_state();
+        let mut new_game_state = GameState {
+            en_passant_square: None,
+            castling_rights: prev_state.castling_rights,
+            halfmove_clock: 0,
+            hash: 0,
+        };
+
+        self.hash ^= prev_state_key ^ self.get_game_state_key(&new_game_state);
+        new_game_state.hash = self.hash;
+        self.state_history.push(new_game_state);
+    }
+
+    pub fn undo_null_move(&mut self) {
+        self.toggle_active_color();
+
+        self.state_history.pop();
+        self.hash = self.get_game_state().hash;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_piece_updates_bitboards() {
+        let mut board = Board::from_fen("8/8/8/8/8/8/8/8 w - - 0 1");
+        let square = Square { file: 3, rank: 4 };
+        board.set_piece(square, Some(Piece::WHITE_KNIGHT));
+        assert_eq!(board.get_piece(square), Some(Piece::WHITE_KNIGHT));
+        assert_eq!(board.get_occupancy(), square_bitboard(square));
+
+        board.set_piece(square, Some(Piece::BLACK_QUEEN));
+        assert_eq!(board.get_piece(square), Some(Piece::BLACK_QUEEN));
+        assert_eq!(board.get_piece_bitboard(Piece::WHITE_KNIGHT), EMPTY);
+        assert_eq!(board.get_color_bitboard(Color::White), EMPTY);
+
+        board.set_piece(square, None);
+        assert_eq!(board.get_occupancy(), EMPTY);
+    }
+
+    #[test]
+    fn test_apply_and_undo_restore_position() {
+        let fen = "r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1";
+        let mut board = Board::from_fen(fen);
+        for m in board.get_legal_moves() {
+            board.apply_move(&m);
+            board.undo_move(&m);
+            assert_eq!(board.to_fen(), fen, "{} did not undo cleanly", m);
         }
-        if let Some((rook_from, rook_to)) = m.castling_rook_from_to {
-            self.set_piece(rook_from, self.get_piece(rook_to));
-            self.set_piece(rook_to, None);
+    }
+
+    #[test]
+    fn test_null_move() {
+        let fen = "4k3/8/8/3pP3/8/8/8/R3K3 w Q d6 0 1";
+        let mut board = Board::from_fen(fen);
+        let hash = board.hash();
+        board.apply_null_move();
+        assert_eq!(board.to_fen(), "4k3/8/8/3pP3/8/8/8/R3K3 b Q - 0 1");
+        assert_eq!(board.hash(), board.compute_hash());
+        board.undo_null_move();
+        assert_eq!(board.to_fen(), fen);
+        assert_eq!(board.hash(), hash);
+
+        assert!(board.has_non_pawn_material(Color::White));
+        assert!(!board.has_non_pawn_material(Color::Black));
+    }
+
+    pub(super) fn play(board: &mut Board, moves: &[&str]) {
+        for notation in moves {
+            let m = Move::from_uci_notation(board, notation).unwrap();
+            board.apply_move(&m);
         }
+    }
 
-        self.state_history.pop();
+    #[test]
+    fn test_hash_is_equal_for_transpositions() {
+        let mut first = Board::default();
+        play(&mut first, &["g1f3", "g8f6", "b1c3", "b8c6"]);
+        let mut second = Board::default();
+        play(&mut second, &["b1c3", "b8c6", "g1f3", "g8f6"]);
+        assert_eq!(first.hash(), second.hash());
+        assert_eq!(first.hash(), first.compute_hash());
+
+        // same placement, but castling rights were lost on the way
+        let mut third = Board::default();
+        play(
+            &mut third,
+            &[
+                "g1f3", "g8f6", "h1g1", "b8c6", "g1h1", "f6g8", "b1c3", "g8f6",
+            ],
+        );
+        assert_eq!(
+            third.to_fen().split(' ').next(),
+            first.to_fen().split(' ').next()
+        );
+        assert_ne!(third.hash(), first.hash());
+    }
+
+    #[test]
+    fn test_hash_matches_fen_and_is_restored_by_undo() {
+        let fen = "r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1";
+        let mut board = Board::from_fen(fen);
+        let hash = board.hash();
+        let pawn_hash = board.pawn_hash();
+        for m in board.get_legal_moves() {
+            board.apply_move(&m);
+            assert_eq!(board.hash(), board.compute_hash(), "{}", m);
+            assert_eq!(board.pawn_hash(), board.compute_pawn_hash(), "{}", m);
+            assert_eq!(
+                board.hash(),
+                Board::from_fen(&board.to_fen()).hash(),
+                "{}",
+                m
+            );
+            board.undo_move(&m);
+            assert_eq!(board.hash(), hash, "{} did not restore the hash", m);
+            assert_eq!(board.pawn_hash(), pawn_hash, "{}", m);
+        }
+    }
+
+    #[test]
+    fn test_promotion_and_en_passant() {
+        let mut board = Board::from_fen("8/1P2k3/8/3pP3/8/8/8/4K3 w - d6 0 1");
+        let moves = board.get_legal_moves();
+
+        let promotion = moves
+            .iter()
+            .find(|m| m.promotion == Some(Piece::WHITE_QUEEN))
+            .unwrap();
+        board.apply_move(promotion);
+        assert_eq!(
+            board.get_piece(Square { file: 1, rank: 7 }),
+            Some(Piece::WHITE_QUEEN)
+        );
+        board.undo_move(promotion);
+
+        let en_passant = moves
+            .iter()
+            .find(|m| m.capture.is_some() && m.en_passant_square.is_some())
+            .unwrap();
+        board.apply_move(en_passant);
+        assert!(board.is_square_empty(Square { file: 3, rank: 4 }));
+        assert_eq!(
+            board.get_piece(Square { file: 3, rank: 5 }),
+            Some(Piece::WHITE_PAWN)
+        );
+    }
+
+    #[test]
+    fn test_pinned_piece_cannot_leave_pin_line() {
+        let board = Board::from_fen("4r1k1/8/8/8/8/8/4B3/4K3 w - - 0 1");
+        let bishop_moves = board
+            .get_legal_moves()
+            .into_iter()
+            .filter(|m| m.piece == Piece::WHITE_BISHOP)
+            .count();
+        assert_eq!(bishop_moves, 0);
     }
 }
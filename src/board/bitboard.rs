@@ -0,0 +1,58 @@
+use crate::board::square::Square;
+
+/// Set of squares, one bit per square. Bit `rank * 8 + file` represents the square.
+pub type Bitboard = u64;
+
+pub const EMPTY: Bitboard = 0;
+pub const FILE_A: Bitboard = 0x0101_0101_0101_0101;
+pub const FILE_H: Bitboard = FILE_A << 7;
+pub const RANK_1: Bitboard = 0xFF;
+pub const RANK_8: Bitboard = RANK_1 << 56;
+pub const LIGHT_SQUARES: Bitboard = 0x55AA_55AA_55AA_55AA;
+pub const NOT_FILE_A: Bitboard = !FILE_A;
+pub const NOT_FILE_H: Bitboard = !FILE_H;
+pub const NOT_FILE_AB: Bitboard = !(FILE_A | (FILE_A << 1));
+pub const NOT_FILE_GH: Bitboard = !(FILE_H | (FILE_H >> 1));
+
+pub fn square_bitboard(square: Square) -> Bitboard {
+    1 << square.to_index()
+}
+
+pub fn first_square(bitboard: Bitboard) -> Option<Square> {
+    if bitboard == EMPTY {
+        None
+    } else {
+        Square::from_index(bitboard.trailing_zeros() as u8)
+    }
+}
+
+/// Iterates over the squares of a bitboard, from a1 to h8.
+pub fn squares(bitboard: Bitboard) -> impl Iterator<Item = Square> {
+    let mut remaining = bitboard;
+    std::iter::from_fn(move || {
+        let square = first_square(remaining)?;
+        remaining &= remaining - 1;
+        Some(square)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_squares_iteration() {
+        let bitboard = square_bitboard(Square { file: 0, rank: 0 })
+            | square_bitboard(Square { file: 4, rank: 3 })
+            | square_bitboard(Square { file: 7, rank: 7 });
+        let collected: Vec<Square> = squares(bitboard).collect();
+        assert_eq!(
+            collected,
+            vec![
+                Square { file: 0, rank: 0 },
+                Square { file: 4, rank: 3 },
+                Square { file: 7, rank: 7 },
+            ]
+        );
+    }
+}
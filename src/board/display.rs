@@ -40,7 +40,7 @@ impl Display for Board {
             }
             board_str.push('\n');
         }
-        board_str.push_str("\n");
+        board_str.push('\n');
 
         board_str.push_str(&format!(
             "Move: {} | Capture clock: {} | Castling rights: {} | En passant: {}\n",
@@ -69,16 +69,23 @@ impl Board {
         let halfmove_clock: u16 = groups[4].parse().unwrap();
         let fullmove_number: u16 = groups[5].parse().unwrap();
 
-        return Board {
-            squares: read_pieces(piece_placement),
-            is_white_turn: active_color == "w",
-            fullmove_number,
-            state_history: vec![GameState {
-                castling_rights: read_castling_rights(castling_rights),
-                en_passant_square,
-                halfmove_clock,
-            }],
-        };
+        let mut board = Board::empty();
+        for (idx, piece) in read_pieces(piece_placement).into_iter().enumerate() {
+            if piece.is_some() {
+                board.set_piece(Square::from_index(idx as u8).unwrap(), piece);
+            }
+        }
+        board.is_white_turn = active_color == "w";
+        board.fullmove_number = fullmove_number;
+        board.state_history.push(GameState {
+            castling_rights: read_castling_rights(castling_rights),
+            en_passant_square,
+            halfmove_clock,
+            hash: 0,
+        });
+        board.hash = board.compute_hash();
+        board.state_history[0].hash = board.hash;
+        board
     }
 
     pub fn to_fen(&self) -> String {
@@ -158,14 +165,14 @@ mod tests {
         assert_eq!(board_array[6], Some(Piece::WHITE_KNIGHT));
         assert_eq!(board_array[7], Some(Piece::WHITE_ROOK));
 
-        for i in 8..16 {
-            assert_eq!(board_array[i], Some(Piece::WHITE_PAWN));
+        for piece in &board_array[8..16] {
+            assert_eq!(*piece, Some(Piece::WHITE_PAWN));
         }
-        for i in 16..48 {
-            assert_eq!(board_array[i], None);
+        for piece in &board_array[16..48] {
+            assert_eq!(*piece, None);
         }
-        for i in 48..56 {
-            assert_eq!(board_array[i], Some(Piece::BLACK_PAWN));
+        for piece in &board_array[48..56] {
+            assert_eq!(*piece, Some(Piece::BLACK_PAWN));
         }
 
         assert_eq!(board_array[56], Some(Piece::BLACK_ROOK));
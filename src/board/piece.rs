@@ -10,6 +10,21 @@ pub enum PieceKind {
     King,
 }
 
+impl PieceKind {
+    pub const ALL: [PieceKind; 6] = [
+        PieceKind::Pawn,
+        PieceKind::Rook,
+        PieceKind::Knight,
+        PieceKind::Bishop,
+        PieceKind::Queen,
+        PieceKind::King,
+    ];
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Color {
     White,
@@ -17,6 +32,12 @@ pub enum Color {
 }
 
 impl Color {
+    pub const ALL: [Color; 2] = [Color::White, Color::Black];
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
     pub fn opposite(&self) -> Color {
         match *self {
             Color::White => Color::Black,
@@ -0,0 +1,238 @@
+use std::fmt::{Display, Formatter};
+
+use crate::board::{
+    Board,
+    bitboard::{EMPTY, LIGHT_SQUARES},
+    piece::{Color, Piece, PieceKind},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawReason {
+    Stalemate,
+    InsufficientMaterial,
+    /// Same position occurred three times, a draw that can be claimed.
+    ThreefoldRepetition,
+    /// Same position occurred five times, drawn automatically.
+    FivefoldRepetition,
+    /// No capture or pawn move in the last 50 moves, a draw that can be claimed.
+    FiftyMoveRule,
+    /// No capture or pawn move in the last 75 moves, drawn automatically.
+    SeventyFiveMoveRule,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameResult {
+    Ongoing,
+    Checkmate { winner: Color },
+    Draw(DrawReason),
+}
+
+impl GameResult {
+    pub fn is_over(&self) -> bool {
+        *self != GameResult::Ongoing
+    }
+}
+
+impl Display for DrawReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            DrawReason::Stalemate => "stalemate",
+            DrawReason::InsufficientMaterial => "insufficient material",
+            DrawReason::ThreefoldRepetition => "threefold repetition",
+            DrawReason::FivefoldRepetition => "fivefold repetition",
+            DrawReason::FiftyMoveRule => "fifty-move rule",
+            DrawReason::SeventyFiveMoveRule => "seventy-five-move rule",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+impl Board {
+    /// Result of the game in the current position. Claimable draws (threefold repetition and
+    /// the fifty-move rule) are reported as draws too.
+    pub fn game_result(&self) -> GameResult {
+        if self.get_legal_moves().is_empty() {
+            return if self.is_in_check() {
+                GameResult::Checkmate {
+                    winner: self.get_active_color().opposite(),
+                }
+            } else {
+                GameResult::Draw(DrawReason::Stalemate)
+            };
+        }
+
+        let halfmove_clock = self.get_game_state().halfmove_clock;
+        let repetitions = self.get_repetition_count();
+        let reason = if self.is_insufficient_material() {
+            DrawReason::InsufficientMaterial
+        } else if halfmove_clock >= 150 {
+            DrawReason::SeventyFiveMoveRule
+        } else if repetitions >= 5 {
+            DrawReason::FivefoldRepetition
+        } else if halfmove_clock >= 100 {
+            DrawReason::FiftyMoveRule
+        } else if repetitions >= 3 {
+            DrawReason::ThreefoldRepetition
+        } else {
+            return GameResult::Ongoing;
+        };
+        GameResult::Draw(reason)
+    }
+
+    /// Cheap draw check for search nodes, which skips move generation and so misses stalemate.
+    /// A position repeated once already counts: if repeating was good for either side, it can
+    /// be repeated again.
+    pub fn is_draw_by_rule(&self) -> bool {
+        self.get_game_state().halfmove_clock >= 100
+            || self.get_repetition_count() >= 2
+            || self.is_insufficient_material()
+    }
+
+    /// Number of times the current position has occurred, the current occurrence included.
+    /// Only positions since the last capture or pawn move are compared, earlier ones can't repeat.
+    pub fn get_repetition_count(&self) -> usize {
+        let current = self.get_game_state();
+        let reversible_plies = (current.halfmove_clock as usize).min(self.state_history.len() - 1);
+        1 + self
+            .state_history
+            .iter()
+            .rev()
+            .take(reversible_plies + 1)
+            .skip(2)
+            .step_by(2)
+            .filter(|game_state| game_state.hash == current.hash)
+            .count()
+    }
+
+    /// Neither side can checkmate: bare kings, a single minor piece, or only bishops all on
+    /// squares of the same color.
+    pub fn is_insufficient_material(&self) -> bool {
+        let mut knights = EMPTY;
+        let mut bishops = EMPTY;
+        for color in Color::ALL {
+            for kind in [PieceKind::Pawn, PieceKind::Rook, PieceKind::Queen] {
+                if self.get_piece_bitboard(Piece::new(color, kind)) != EMPTY {
+                    return false;
+                }
+            }
+            knights |= self.get_piece_bitboard(Piece::new(color, PieceKind::Knight));
+            bishops |= self.get_piece_bitboard(Piece::new(color, PieceKind::Bishop));
+        }
+
+        (knights | bishops).count_ones() <= 1
+            || knights == EMPTY
+                && (bishops & LIGHT_SQUARES == EMPTY || bishops & !LIGHT_SQUARES == EMPTY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::tests::play;
+
+    #[test]
+    fn test_checkmate_and_stalemate() {
+        let board = Board::from_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1");
+        assert_eq!(
+            board.game_result(),
+            GameResult::Checkmate {
+                winner: Color::White
+            }
+        );
+
+        let board = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+        assert_eq!(board.game_result(), GameResult::Draw(DrawReason::Stalemate));
+        assert!(board.is_draw());
+    }
+
+    #[test]
+    fn test_repetition() {
+        let mut board = Board::default();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        assert_eq!(board.get_repetition_count(), 1);
+
+        play(&mut board, &shuffle);
+        assert_eq!(board.get_repetition_count(), 2);
+        assert_eq!(board.game_result(), GameResult::Ongoing);
+
+        play(&mut board, &shuffle);
+        assert_eq!(board.get_repetition_count(), 3);
+        assert_eq!(
+            board.game_result(),
+            GameResult::Draw(DrawReason::ThreefoldRepetition)
+        );
+
+        play(&mut board, &shuffle);
+        play(&mut board, &shuffle);
+        assert_eq!(
+            board.game_result(),
+            GameResult::Draw(DrawReason::FivefoldRepetition)
+        );
+
+        // a pawn move makes earlier positions unreachable
+        play(&mut board, &["e2e4"]);
+        assert_eq!(board.get_repetition_count(), 1);
+        assert_eq!(board.game_result(), GameResult::Ongoing);
+    }
+
+    #[test]
+    fn test_repetition_after_double_push() {
+        // no black pawn can take en passant after e2e4, so the position repeats once the knights
+        // are back even though the en passant square is gone
+        let mut board = Board::from_fen("6nk/8/8/8/8/8/4P3/6NK w - - 0 1");
+        play(&mut board, &["e2e4", "g8f6", "g1f3", "f6g8", "f3g1"]);
+        assert_eq!(board.get_repetition_count(), 2);
+
+        // here d4 could take on e3, which it no longer can after the shuffle
+        let mut board = Board::from_fen("6nk/8/8/8/3p4/8/4P3/6NK w - - 0 1");
+        play(&mut board, &["e2e4", "g8f6", "g1f3", "f6g8", "f3g1"]);
+        assert_eq!(board.get_repetition_count(), 1);
+    }
+
+    #[test]
+    fn test_move_rules() {
+        let mut board = Board::from_fen("4k3/4p3/8/8/8/8/4P3/R3K3 w - - 99 80");
+        assert_eq!(board.game_result(), GameResult::Ongoing);
+        play(&mut board, &["a1a2"]);
+        assert_eq!(
+            board.game_result(),
+            GameResult::Draw(DrawReason::FiftyMoveRule)
+        );
+        play(&mut board, &["e7e5"]);
+        assert_eq!(board.get_game_state().halfmove_clock, 0);
+
+        let board = Board::from_fen("4k3/4p3/8/8/8/8/4P3/R3K3 w - - 150 120");
+        assert_eq!(
+            board.game_result(),
+            GameResult::Draw(DrawReason::SeventyFiveMoveRule)
+        );
+    }
+
+    #[test]
+    fn test_insufficient_material() {
+        for fen in [
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/1N2K3 b - - 0 1",
+            "2b1k3/8/8/8/8/8/8/3BK3 w - - 0 1",
+        ] {
+            let board = Board::from_fen(fen);
+            assert_eq!(
+                board.game_result(),
+                GameResult::Draw(DrawReason::InsufficientMaterial),
+                "{}",
+                fen
+            );
+        }
+
+        for fen in [
+            "4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1",
+            "4k3/8/8/8/8/8/8/1NB1K3 w - - 0 1",
+            "1n2k3/8/8/8/8/8/8/1N2K3 w - - 0 1",
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        ] {
+            let board = Board::from_fen(fen);
+            assert_eq!(board.game_result(), GameResult::Ongoing, "{}", fen);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{
+    board::{
+        Board,
+        bitboard::{Bitboard, EMPTY, first_square, square_bitboard},
+        piece::{Color, Piece, PieceKind},
+        square::Square,
+    },
+    r#move::{Move, get_square_attackers_with_occupancy},
+};
+
+/// Pieces in the order they join an exchange, least valuable first.
+const EXCHANGE_ORDER: [PieceKind; 6] = [
+    PieceKind::Pawn,
+    PieceKind::Knight,
+    PieceKind::Bishop,
+    PieceKind::Rook,
+    PieceKind::Queen,
+    PieceKind::King,
+];
+
+/// Value of a piece in an exchange, in centipawns. The king is never captured, its value only
+/// needs to be higher than the others.
+fn exchange_value(kind: PieceKind) -> i32 {
+    match kind {
+        PieceKind::Pawn => 100,
+        PieceKind::Knight => 300,
+        PieceKind::Bishop => 300,
+        PieceKind::Rook => 500,
+        PieceKind::Queen => 900,
+        PieceKind::King => 10000,
+    }
+}
+
+impl Board {
+    /// Static exchange evaluation: material won by `m`, in centipawns, when both sides then keep
+    /// capturing on its destination with their least valuable piece, each side being free to stop
+    /// when that suits it. Pieces behind sliders join in once the pieces in front of them have
+    /// captured. Pins are ignored, and a king only captures when the other side has no attacker
+    /// left. A quiet move scores minus the value of the moved piece when it can be won for less.
+    pub fn see(&self, m: &Move) -> i32 {
+        // Material balance after each capture of the exchange, for the side making it
+        let mut gains = [0; 32];
+        let mut occupancy = self.get_occupancy() & !square_bitboard(m.from);
+        if let Some(captured) = m.capture {
+            gains[0] = exchange_value(captured.get_kind());
+            // En passant captures a pawn beside the destination
+            if let Some(captured_square) = m.en_passant_square {
+                occupancy &= !square_bitboard(captured_square);
+            }
+        }
+        if let Some(promotion) = m.promotion {
+            gains[0] += exchange_value(promotion.get_kind()) - exchange_value(PieceKind::Pawn);
+        }
+
+        let mut on_square = exchange_value(m.promotion.unwrap_or(m.piece).get_kind());
+        let mut side = m.piece.get_color().opposite();
+        let mut depth = 0;
+        while let Some((kind, square)) = self.get_least_valuable_attacker(m.to, side, occupancy) {
+            let remaining = occupancy & !square_bitboard(square);
+            if kind == PieceKind::King
+                && self.get_exchange_attackers(m.to, side.opposite(), remaining) != EMPTY
+            {
+                break;
+            }
+            depth += 1;
+            gains[depth] = on_square - gains[depth - 1];
+            on_square = exchange_value(kind);
+            occupancy = remaining;
+            side = side.opposite();
+        }
+
+        // Each side only captures when it does better than stopping
+        while depth > 0 {
+            gains[depth - 1] = -(-gains[depth - 1]).max(gains[depth]);
+            depth -= 1;
+        }
+        gains[0]
+    }
+
+    /// Whether [`Board::see`] of `m` is at least `threshold`.
+    pub fn see_ge(&self, m: &Move, threshold: i32) -> bool {
+        self.see(m) >= threshold
+    }
+
+    /// Pieces of `color` still in `occupancy` that attack `square`.
+    fn get_exchange_attackers(
+        &self,
+        square: Square,
+        color: Color,
+        occupancy: Bitboard,
+    ) -> Bitboard {
+        get_square_attackers_with_occupancy(self, square, color.opposite(), occupancy) & occupancy
+    }
+
+    fn get_least_valuable_attacker(
+        &self,
+        square: Square,
+        color: Color,
+        occupancy: Bitboard,
+    ) -> Option<(PieceKind, Square)> {
+        let attackers = self.get_exchange_attackers(square, color, occupancy);
+        EXCHANGE_ORDER.into_iter().find_map(|kind| {
+            first_square(attackers & self.get_piece_bitboard(Piece::new(color, kind)))
+                .map(|square| (kind, square))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn see(fen: &str, notation: &str) -> i32 {
+        let board = Board::from_fen(fen);
+        board.see(&Move::from_uci_notation(&board, notation).unwrap())
+    }
+
+    #[test]
+    fn test_see() {
+        // undefended pawn
+        assert_eq!(
+            see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"),
+            100
+        );
+        // knight for a pawn, recapturing would lose more
+        assert_eq!(
+            see(
+                "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+                "d3e5"
+            ),
+            -200
+        );
+        // the rook behind the first one recaptures
+        assert_eq!(see("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5"), 100);
+        assert_eq!(see("3rk3/8/8/3p4/8/8/3R4/4K3 w - - 0 1", "d2d5"), -400);
+        // the king can only recapture an undefended piece
+        assert_eq!(see("8/8/4k3/3r4/8/8/8/3QK3 w - - 0 1", "d1d5"), -400);
+        assert_eq!(see("8/8/4k3/3r4/2P5/8/8/3QK3 w - - 0 1", "d1d5"), 500);
+        // quiet moves and promotions
+        assert_eq!(see("4k3/8/8/8/8/2p5/8/1N5K w - - 0 1", "b1d2"), -300);
+        assert_eq!(see("4k3/8/8/8/8/2p5/8/1N5K w - - 0 1", "b1a3"), 0);
+        assert_eq!(see("8/1P2k3/8/8/8/8/8/4K3 w - - 0 1", "b7b8q"), 800);
+        assert_eq!(see("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), -100);
+        // en passant, the captured pawn no longer blocks the rook behind
+        assert_eq!(see("3rk3/8/8/3pP3/8/8/8/3RK3 w - d6 0 1", "e5d6"), 100);
+
+        let board = Board::from_fen("3rk3/8/8/3p4/8/8/3R4/4K3 w - - 0 1");
+        let m = Move::from_uci_notation(&board, "d2d5").unwrap();
+        assert!(board.see_ge(&m, -400));
+        assert!(!board.see_ge(&m, 0));
+    }
+}
@@ -0,0 +1,98 @@
+use crate::board::{
+    CastlingRights,
+    piece::{Color, Piece},
+    square::Square,
+};
+
+pub struct ZobristKeys {
+    pieces: [[[u64; 64]; 6]; 2],
+    black_to_move: u64,
+    castling: [u64; 4],
+    en_passant_file: [u64; 8],
+}
+
+/// Keys are generated at compile time from a fixed seed, so hashes are stable between runs.
+pub const KEYS: ZobristKeys = ZobristKeys::generate();
+
+impl ZobristKeys {
+    const fn generate() -> Self {
+        let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+        let mut keys = ZobristKeys {
+            pieces: [[[0; 64]; 6]; 2],
+            black_to_move: 0,
+            castling: [0; 4],
+            en_passant_file: [0; 8],
+        };
+
+        let mut color = 0;
+        while color < 2 {
+            let mut kind = 0;
+            while kind < 6 {
+                let mut square = 0;
+                while square < 64 {
+                    keys.pieces[color][kind][square] = next_random(&mut state);
+                    square += 1;
+                }
+                kind += 1;
+            }
+            color += 1;
+        }
+
+        keys.black_to_move = next_random(&mut state);
+
+        let mut idx = 0;
+        while idx < 4 {
+            keys.castling[idx] = next_random(&mut state);
+            idx += 1;
+        }
+
+        let mut file = 0;
+        while file < 8 {
+            keys.en_passant_file[file] = next_random(&mut state);
+            file += 1;
+        }
+
+        keys
+    }
+
+    pub fn piece(&self, piece: Piece, square: Square) -> u64 {
+        self.pieces[piece.get_color().index()][piece.get_kind().index()][square.to_index()]
+    }
+
+    pub fn side_to_move(&self, color: Color) -> u64 {
+        match color {
+            Color::White => 0,
+            Color::Black => self.black_to_move,
+        }
+    }
+
+    pub fn castling(&self, rights: &CastlingRights) -> u64 {
+        let mut key = 0;
+        for (idx, has_right) in [
+            rights.white_king_side,
+            rights.white_queen_side,
+            rights.black_king_side,
+            rights.black_queen_side,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            if has_right {
+                key ^= self.castling[idx];
+            }
+        }
+        key
+    }
+
+    pub fn en_passant(&self, square: Option<Square>) -> u64 {
+        square.map_or(0, |sq| self.en_passant_file[sq.file as usize])
+    }
+}
+
+/// xorshift64* step
+const fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state >> 12;
+    *state ^= *state << 25;
+    *state ^= *state >> 27;
+    state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+}
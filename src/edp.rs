@@ -7,9 +7,11 @@ pub struct EDP {
     pub id: Option<String>, // position identification
     pub c0: Option<String>, // comment
     pub am: Option<String>, // avoid move
+    pub dm: Option<u8>,     // direct mate in this many moves
 }
 
 impl EDP {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(edp: &str) -> (Board, Self) {
         let parts: Vec<&str> = edp.splitn(5, ' ').collect();
         let fen = parts
@@ -36,6 +38,7 @@ impl EDP {
                 "id" => ops.id = Some(value.to_string()),
                 "c0" => ops.c0 = Some(value.to_string()),
                 "am" => ops.am = Some(value.to_string()),
+                "dm" => ops.dm = Some(value.parse().expect("Failed to parse direct mate")),
                 _ => eprintln!("Unknown EDP operation: {}", key),
             }
         }
@@ -65,4 +68,11 @@ mod tests {
             "r1bqkbnr/pppppppp/n7/8/8/5N2/PPPPPPPP/RNBQKB1R w KQkq - 0 1"
         );
     }
+
+    #[test]
+    fn test_edp_direct_mate() {
+        let (_, ops) = EDP::from_str("6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8; dm 1; id mate1;");
+        assert_eq!(ops.bm, Some("Ra8".to_string()));
+        assert_eq!(ops.dm, Some(1));
+    }
 }
@@ -1,14 +1,42 @@
+mod king_safety;
 mod material;
 mod mobility;
+mod pawn_structure;
+mod pieces;
 mod positioning;
 mod sum;
+mod tapered;
 
+pub use king_safety::KingSafetyEvaluator;
 pub use material::MaterialEvaluator;
+pub use mobility::MobilityEvaluator;
+pub use pawn_structure::PawnStructureEvaluator;
+pub use pieces::{PieceTerm, PiecesEvaluator};
 pub use positioning::PositioningEvaluator;
 pub use sum::SumEvaluator;
+pub use tapered::{MAX_PHASE, TaperedScore, game_phase};
+
+use crate::board::piece::PieceKind;
 
 pub trait Evaluator: Send + Sync {
     fn evaluate(&self, board: &crate::Board) -> i32;
+    /// Middlegame and endgame scores, relative to the side to move like [`Evaluator::evaluate`],
+    /// which blends them by [`game_phase`]. Both are the plain evaluation for evaluators that
+    /// don't tell the phases apart.
+    fn evaluate_tapered(&self, board: &crate::Board) -> TaperedScore {
+        let score = self.evaluate(board);
+        TaperedScore::new(score, score)
+    }
+    /// Middlegame and endgame scores of each term of the evaluation, by name. A single term
+    /// named after the evaluator for evaluators that don't split their score.
+    fn evaluate_breakdown(&self, board: &crate::Board) -> Vec<(String, TaperedScore)> {
+        vec![(self.name(), self.evaluate_tapered(board))]
+    }
+    /// Value of a piece in the units of this evaluator, used by search to judge whether winning
+    /// it can matter. Zero for evaluators that don't score material.
+    fn piece_value(&self, _kind: PieceKind) -> i32 {
+        0
+    }
     fn evaluate_for_white(&self, board: &crate::Board) -> i32 {
         self.evaluate(board) * board.get_active_color().get_value()
     }
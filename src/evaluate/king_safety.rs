@@ -0,0 +1,166 @@
+use crate::Board;
+use crate::attacks::{bishop_attacks, king_attacks, knight_attacks, queen_attacks, rook_attacks};
+use crate::board::bitboard::{Bitboard, EMPTY, FILE_A, square_bitboard, squares};
+use crate::board::piece::{Color, Piece, PieceKind};
+use crate::board::square::Square;
+use crate::evaluate::{Evaluator, TaperedScore, game_phase};
+
+/// Scores in centipawns, for the middlegame only. Shield and storm tables are indexed by how many
+/// ranks in front of the king the pawn stands, storms counting the closest enemy pawn of a file.
+const SHIELD_BONUS: [i32; 3] = [0, 12, 6];
+const STORM_PENALTY: [i32; 5] = [0, -5, -20, -10, -5];
+const SEMI_OPEN_FILE_PENALTY: i32 = -12;
+const OPEN_FILE_PENALTY: i32 = -20;
+/// Weight of an enemy piece attacking the king zone, plus a weight for each square of the zone
+/// it attacks. Their sum is scaled by the number of attackers, as a lone piece can rarely mate.
+const ATTACKER_WEIGHT: [(PieceKind, i32); 4] = [
+    (PieceKind::Knight, 20),
+    (PieceKind::Bishop, 20),
+    (PieceKind::Rook, 40),
+    (PieceKind::Queen, 80),
+];
+const ATTACKED_SQUARE_WEIGHT: i32 = 5;
+const ATTACKER_COUNT_PERCENT: [i32; 8] = [0, 0, 50, 75, 88, 94, 97, 99];
+
+/// Scores the shelter of each king: its pawn shield, the enemy pawns storming it, the open files
+/// next to it and the enemy pieces attacking the squares around it. All of it only matters in the
+/// middlegame, the endgame score is zero.
+pub struct KingSafetyEvaluator {
+    weight: i32,
+}
+
+impl KingSafetyEvaluator {
+    pub fn new(weight: i32) -> Self {
+        Self { weight }
+    }
+}
+
+impl Evaluator for KingSafetyEvaluator {
+    fn name(&self) -> String {
+        "king safety".to_string()
+    }
+    fn evaluate(&self, board: &Board) -> i32 {
+        self.evaluate_tapered(board).taper(game_phase(board))
+    }
+    fn evaluate_tapered(&self, board: &Board) -> TaperedScore {
+        let score = king_safety(board, Color::White) - king_safety(board, Color::Black);
+        TaperedScore::new(score, 0) * (board.get_active_color().get_value() * self.weight)
+    }
+}
+
+/// Middlegame safety of the king of `color`, positive when sheltered.
+fn king_safety(board: &Board, color: Color) -> i32 {
+    let Some(king) = board.get_king_square(color) else {
+        return 0;
+    };
+    pawn_shelter(board, color, king) - attack_penalty(board, color, king)
+}
+
+/// Shield, storm and open file terms on the file of the king and the ones next to it.
+fn pawn_shelter(board: &Board, color: Color, king: Square) -> i32 {
+    let pawns = board.get_piece_bitboard(Piece::new(color, PieceKind::Pawn));
+    let enemy_pawns = board.get_piece_bitboard(Piece::new(color.opposite(), PieceKind::Pawn));
+    let mut score = 0;
+    for file in king.file.saturating_sub(1)..=(king.file + 1).min(7) {
+        let file_mask = FILE_A << file;
+        if let Some(distance) = closest_pawn_ahead(pawns & file_mask, king, color) {
+            score += SHIELD_BONUS.get(distance).copied().unwrap_or(0);
+        } else if enemy_pawns & file_mask == EMPTY {
+            score += OPEN_FILE_PENALTY;
+        } else {
+            score += SEMI_OPEN_FILE_PENALTY;
+        }
+        if let Some(distance) = closest_pawn_ahead(enemy_pawns & file_mask, king, color) {
+            score += STORM_PENALTY.get(distance).copied().unwrap_or(0);
+        }
+    }
+    score
+}
+
+/// Number of ranks between the king and the closest of `pawns` in front of it, from the point of
+/// view of `color`.
+fn closest_pawn_ahead(pawns: Bitboard, king: Square, color: Color) -> Option<usize> {
+    squares(pawns)
+        .map(|square| (square.rank as i32 - king.rank as i32) * color.get_value())
+        .filter(|&distance| distance > 0)
+        .min()
+        .map(|distance| distance as usize)
+}
+
+/// Penalty for the enemy pieces attacking the king zone: the king square and the squares next to
+/// it.
+fn attack_penalty(board: &Board, color: Color, king: Square) -> i32 {
+    let zone = king_attacks(king) | square_bitboard(king);
+    let occupancy = board.get_occupancy();
+    let mut attackers = 0;
+    let mut weight = 0;
+    for (kind, kind_weight) in ATTACKER_WEIGHT {
+        let pieces = board.get_piece_bitboard(Piece::new(color.opposite(), kind));
+        for square in squares(pieces) {
+            let attacks = match kind {
+                PieceKind::Knight => knight_attacks(square),
+                PieceKind::Bishop => bishop_attacks(square, occupancy),
+                PieceKind::Rook => rook_attacks(square, occupancy),
+                _ => queen_attacks(square, occupancy),
+            } & zone;
+            if attacks != EMPTY {
+                attackers += 1;
+                weight += kind_weight + ATTACKED_SQUARE_WEIGHT * attacks.count_ones() as i32;
+            }
+        }
+    }
+    weight * ATTACKER_COUNT_PERCENT[attackers.min(7)] / 100
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pawn_shelter() {
+        let evaluator = KingSafetyEvaluator::new(1);
+        let sheltered =
+            Board::from_fen("rnbq1rk1/ppppbppp/5n2/4p3/4P3/5N2/PPPPBPPP/RNBQ1RK1 w - - 0 1");
+        assert_eq!(evaluator.evaluate(&sheltered), 0);
+        // white pushed the pawns in front of its king
+        let weakened =
+            Board::from_fen("rnbq1rk1/ppppbppp/5n2/4p3/4P1PP/5N2/PPPPBP2/RNBQ1RK1 w - - 0 1");
+        assert!(evaluator.evaluate(&weakened) < 0);
+        // and worse still without them, on files half-open for the enemy rooks
+        let open = Board::from_fen("rnbq1rk1/ppppbppp/5n2/4p3/4P3/5N2/PPPPBP2/RNBQ1RK1 w - - 0 1");
+        assert!(evaluator.evaluate(&open) < evaluator.evaluate(&weakened));
+    }
+
+    #[test]
+    fn test_attackers() {
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1");
+        assert_eq!(
+            attack_penalty(&board, Color::White, Square::new(6, 0).unwrap()),
+            0
+        );
+        // a single attacker isn't enough to count
+        let board = Board::from_fen("6k1/5ppp/8/8/8/7q/5PPP/6K1 w - - 0 1");
+        assert_eq!(
+            attack_penalty(&board, Color::White, Square::new(6, 0).unwrap()),
+            0
+        );
+        let board = Board::from_fen("6k1/5ppp/8/8/6n1/7q/5PPP/6K1 w - - 0 1");
+        assert!(attack_penalty(&board, Color::White, Square::new(6, 0).unwrap()) > 0);
+    }
+
+    #[test]
+    fn test_fades_out_in_endgame() {
+        let evaluator = KingSafetyEvaluator::new(1);
+        // same open king, with and without the pieces
+        let middlegame = Board::from_fen("r2q1rk1/ppp2ppp/2n5/8/8/8/8/R2Q1RK1 w - - 0 1");
+        let endgame = Board::from_fen("6k1/ppp2ppp/8/8/8/8/8/6K1 w - - 0 1");
+        assert!(evaluator.evaluate(&middlegame) < 0);
+        assert_eq!(evaluator.evaluate_tapered(&endgame).eg, 0);
+        assert_eq!(evaluator.evaluate(&endgame), 0);
+        let black_to_move = Board::from_fen("r2q1rk1/ppp2ppp/2n5/8/8/8/8/R2Q1RK1 b - - 0 1");
+        assert_eq!(
+            evaluator.evaluate(&black_to_move),
+            -evaluator.evaluate(&middlegame)
+        );
+    }
+}
@@ -1,8 +1,7 @@
 use crate::Board;
-use crate::board::piece::{Color, PieceKind};
-use crate::board::square::Square;
+use crate::board::piece::{Color, Piece, PieceKind};
 
-use super::Evaluator;
+use super::{Evaluator, TaperedScore, game_phase};
 
 pub struct MaterialEvaluator {
     weight: i32,
@@ -12,31 +11,45 @@ impl MaterialEvaluator {
     pub fn new(weight: i32) -> Self {
         Self { weight }
     }
+
+    /// Middlegame and endgame values of a piece. Pawns and rooks gain value as the board empties,
+    /// minor pieces lose some.
+    fn tapered_value(&self, kind: PieceKind) -> TaperedScore {
+        let value = match kind {
+            PieceKind::Pawn => TaperedScore::new(100, 120),
+            PieceKind::Knight => TaperedScore::new(320, 300),
+            PieceKind::Bishop => TaperedScore::new(330, 320),
+            PieceKind::Rook => TaperedScore::new(500, 540),
+            PieceKind::Queen => TaperedScore::new(900, 940),
+            PieceKind::King => TaperedScore::new(20000, 20000),
+        };
+        value * self.weight
+    }
 }
 
 impl Evaluator for MaterialEvaluator {
     fn name(&self) -> String {
         "material".to_string()
     }
+    /// Middlegame value of the piece.
+    fn piece_value(&self, kind: PieceKind) -> i32 {
+        self.tapered_value(kind).mg
+    }
     fn evaluate(&self, board: &Board) -> i32 {
-        let mut score = 0;
-        for idx in 0..64 {
-            if let Some(piece) = board.get_piece(Square::from_index(idx).unwrap()) {
-                let piece_value = match piece.get_kind() {
-                    PieceKind::Pawn => 100,
-                    PieceKind::Knight => 320,
-                    PieceKind::Bishop => 330,
-                    PieceKind::Rook => 500,
-                    PieceKind::Queen => 900,
-                    PieceKind::King => 20000,
-                };
-                if piece.get_color() == Color::White {
-                    score += piece_value;
-                } else {
-                    score -= piece_value;
-                }
-            }
+        self.evaluate_tapered(board).taper(game_phase(board))
+    }
+    fn evaluate_tapered(&self, board: &Board) -> TaperedScore {
+        let mut score = TaperedScore::default();
+        for kind in PieceKind::ALL {
+            let piece_value = self.tapered_value(kind);
+            let white_count = board
+                .get_piece_bitboard(Piece::new(Color::White, kind))
+                .count_ones();
+            let black_count = board
+                .get_piece_bitboard(Piece::new(Color::Black, kind))
+                .count_ones();
+            score += piece_value * (white_count as i32 - black_count as i32);
         }
-        score * board.get_active_color().get_value() * self.weight
+        score * board.get_active_color().get_value()
     }
 }
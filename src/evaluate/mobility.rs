@@ -1,13 +1,104 @@
-use super::Evaluator;
-use crate::{Board, board::piece::Color};
+use super::{Evaluator, TaperedScore, game_phase};
+use crate::attacks::{bishop_attacks, knight_attacks, pawn_attacks, queen_attacks, rook_attacks};
+use crate::board::bitboard::{Bitboard, EMPTY, squares};
+use crate::board::piece::{Color, Piece, PieceKind};
+use crate::{Board, board::square::Square};
 
-#[allow(dead_code)]
+/// Scores in centipawns by number of reachable squares, from 0 up to the most a piece of the kind
+/// can reach on an empty board.
+const KNIGHT_MOBILITY: [TaperedScore; 9] = tapered([
+    (-30, -40),
+    (-15, -20),
+    (-5, -10),
+    (0, 0),
+    (5, 5),
+    (10, 10),
+    (15, 15),
+    (18, 18),
+    (20, 20),
+]);
+const BISHOP_MOBILITY: [TaperedScore; 14] = tapered([
+    (-25, -30),
+    (-12, -15),
+    (-2, -5),
+    (3, 2),
+    (8, 8),
+    (13, 14),
+    (17, 19),
+    (20, 23),
+    (23, 26),
+    (25, 28),
+    (27, 30),
+    (29, 32),
+    (30, 33),
+    (31, 34),
+]);
+const ROOK_MOBILITY: [TaperedScore; 15] = tapered([
+    (-15, -35),
+    (-8, -18),
+    (-4, -8),
+    (-2, 0),
+    (0, 6),
+    (2, 12),
+    (4, 17),
+    (6, 22),
+    (8, 27),
+    (10, 31),
+    (12, 34),
+    (13, 37),
+    (14, 39),
+    (15, 41),
+    (16, 42),
+]);
+const QUEEN_MOBILITY: [TaperedScore; 28] = tapered([
+    (-15, -25),
+    (-10, -18),
+    (-6, -12),
+    (-3, -7),
+    (-1, -3),
+    (0, 0),
+    (1, 3),
+    (2, 6),
+    (3, 9),
+    (4, 12),
+    (5, 15),
+    (6, 18),
+    (7, 20),
+    (8, 22),
+    (9, 24),
+    (10, 26),
+    (11, 28),
+    (12, 30),
+    (13, 32),
+    (14, 34),
+    (15, 36),
+    (16, 37),
+    (17, 38),
+    (18, 39),
+    (19, 40),
+    (20, 41),
+    (21, 42),
+    (22, 43),
+]);
+
+const fn tapered<const N: usize>(values: [(i32, i32); N]) -> [TaperedScore; N] {
+    let mut scores = [TaperedScore::new(0, 0); N];
+    let mut i = 0;
+    while i < N {
+        scores[i] = TaperedScore::new(values[i].0, values[i].1);
+        i += 1;
+    }
+    scores
+}
+
+/// Scores the squares each knight, bishop, rook and queen attacks, leaving out the ones holding
+/// pieces of its color or attacked by enemy pawns, where it could hardly go. Computed from the
+/// attack tables rather than generating moves, so that it is cheap enough to run at every node.
 pub struct MobilityEvaluator {
     weight: i32,
 }
 
 impl MobilityEvaluator {
-    #[allow(dead_code)]
     pub fn new(weight: i32) -> Self {
         Self { weight }
     }
@@ -17,14 +108,74 @@ impl Evaluator for MobilityEvaluator {
     fn name(&self) -> String {
         "mobility".to_string()
     }
-    /**
-    Use with caution. Legal move calculation is currently very compute expensive.
-    */
     fn evaluate(&self, board: &Board) -> i32 {
-        let white_moves = board.get_legal_moves_for_color(Color::White);
-        let black_moves = board.get_legal_moves_for_color(Color::Black);
-        (white_moves.len() as i32 - black_moves.len() as i32)
-            * self.weight
-            * board.get_active_color().get_value()
+        self.evaluate_tapered(board).taper(game_phase(board))
+    }
+    fn evaluate_tapered(&self, board: &Board) -> TaperedScore {
+        let score = mobility(board, Color::White) - mobility(board, Color::Black);
+        score * (board.get_active_color().get_value() * self.weight)
+    }
+}
+
+/// Mobility score of the pieces of `color`.
+fn mobility(board: &Board, color: Color) -> TaperedScore {
+    let occupancy = board.get_occupancy();
+    let area = !(board.get_color_bitboard(color) | pawn_controlled(board, color.opposite()));
+    let mut score = TaperedScore::default();
+    for (kind, table) in [
+        (PieceKind::Knight, &KNIGHT_MOBILITY[..]),
+        (PieceKind::Bishop, &BISHOP_MOBILITY[..]),
+        (PieceKind::Rook, &ROOK_MOBILITY[..]),
+        (PieceKind::Queen, &QUEEN_MOBILITY[..]),
+    ] {
+        for square in squares(board.get_piece_bitboard(Piece::new(color, kind))) {
+            let count = (attacks(kind, square, occupancy) & area).count_ones() as usize;
+            score += table[count];
+        }
+    }
+    score
+}
+
+fn attacks(kind: PieceKind, square: Square, occupancy: Bitboard) -> Bitboard {
+    match kind {
+        PieceKind::Knight => knight_attacks(square),
+        PieceKind::Bishop => bishop_attacks(square, occupancy),
+        PieceKind::Rook => rook_attacks(square, occupancy),
+        _ => queen_attacks(square, occupancy),
+    }
+}
+
+/// Squares attacked by the pawns of `color`.
+fn pawn_controlled(board: &Board, color: Color) -> Bitboard {
+    squares(board.get_piece_bitboard(Piece::new(color, PieceKind::Pawn)))
+        .fold(EMPTY, |controlled, square| {
+            controlled | pawn_attacks(square, color)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mobility() {
+        let evaluator = MobilityEvaluator::new(1);
+        assert_eq!(evaluator.evaluate(&Board::default()), 0);
+
+        // a knight in the centre against one in the corner
+        let board = Board::from_fen("n3k3/8/8/8/3N4/8/8/4K3 w - - 0 1");
+        assert_eq!(
+            evaluator.evaluate_tapered(&board),
+            KNIGHT_MOBILITY[8] - KNIGHT_MOBILITY[2]
+        );
+        let board = Board::from_fen("n3k3/8/8/8/3N4/8/8/4K3 b - - 0 1");
+        assert_eq!(
+            evaluator.evaluate_tapered(&board),
+            KNIGHT_MOBILITY[2] - KNIGHT_MOBILITY[8]
+        );
+
+        // squares attacked by enemy pawns and the ones of its own pieces don't count
+        let board = Board::from_fen("4k3/8/2p5/8/3N4/1P6/8/4K3 w - - 0 1");
+        assert_eq!(evaluator.evaluate_tapered(&board), KNIGHT_MOBILITY[6]);
     }
 }
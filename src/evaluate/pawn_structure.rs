@@ -0,0 +1,376 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::Board;
+use crate::attacks::pawn_attacks;
+use crate::board::bitboard::{Bitboard, EMPTY, FILE_A, RANK_1, squares};
+use crate::board::piece::{Color, Piece, PieceKind};
+use crate::board::square::Square;
+use crate::evaluate::{Evaluator, TaperedScore, game_phase};
+
+/// Entries in the pawn cache of each evaluator.
+const PAWN_CACHE_SIZE: usize = 1 << 14;
+
+/// Scores in centipawns. Per-rank tables are indexed by the rank counted from the side of the
+/// pawn, 1 being its starting rank.
+const DOUBLED_PENALTY: TaperedScore = TaperedScore::new(-10, -20);
+const ISOLATED_PENALTY: TaperedScore = TaperedScore::new(-10, -15);
+const BACKWARD_PENALTY: TaperedScore = TaperedScore::new(-8, -10);
+const CONNECTED_BONUS: [i32; 8] = [0, 3, 5, 8, 12, 20, 30, 0];
+const PASSED_BONUS: [TaperedScore; 8] = [
+    TaperedScore::new(0, 0),
+    TaperedScore::new(5, 10),
+    TaperedScore::new(5, 15),
+    TaperedScore::new(10, 25),
+    TaperedScore::new(20, 45),
+    TaperedScore::new(35, 75),
+    TaperedScore::new(60, 120),
+    TaperedScore::new(0, 0),
+];
+/// Endgame terms of passed pawns, multiplied by a factor growing as the pawn advances: a bonus
+/// when no piece stands in front of it, and the distances of both kings to the square in front
+/// of it, the farther the enemy king and the closer its own king the better.
+const PASSED_RANK_FACTOR: [i32; 8] = [0, 0, 0, 1, 2, 3, 5, 0];
+const FREE_PATH_BONUS: i32 = 6;
+const ENEMY_KING_DISTANCE_BONUS: i32 = 4;
+const OWN_KING_DISTANCE_PENALTY: i32 = 2;
+
+/// Scores doubled, isolated, backward, connected and passed pawns, plus endgame terms for passed
+/// pawns depending on the other pieces. The pawns-only part is cached by pawn key, as the pawns
+/// change much less often than the rest of the position during search.
+pub struct PawnStructureEvaluator {
+    weight: i32,
+    cache: PawnCache,
+}
+
+impl PawnStructureEvaluator {
+    pub fn new(weight: i32) -> Self {
+        Self {
+            weight,
+            cache: PawnCache::new(),
+        }
+    }
+
+    fn get_entry(&self, board: &Board) -> PawnEntry {
+        let key = board.pawn_hash();
+        self.cache.probe(key).unwrap_or_else(|| {
+            let entry = PawnEntry::compute(board);
+            self.cache.store(key, &entry);
+            entry
+        })
+    }
+}
+
+impl Evaluator for PawnStructureEvaluator {
+    fn name(&self) -> String {
+        "pawn structure".to_string()
+    }
+    fn evaluate(&self, board: &Board) -> i32 {
+        self.evaluate_tapered(board).taper(game_phase(board))
+    }
+    fn evaluate_tapered(&self, board: &Board) -> TaperedScore {
+        let entry = self.get_entry(board);
+        let score =
+            entry.score + TaperedScore::new(0, passed_pawn_endgame_score(board, entry.passed));
+        score * (board.get_active_color().get_value() * self.weight)
+    }
+}
+
+/// Pawn structure of a position: its score for White, and the passed pawns of both sides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PawnEntry {
+    score: TaperedScore,
+    passed: Bitboard,
+}
+
+impl PawnEntry {
+    fn compute(board: &Board) -> Self {
+        let mut entry = PawnEntry {
+            score: TaperedScore::default(),
+            passed: EMPTY,
+        };
+        for color in Color::ALL {
+            let pawns = board.get_piece_bitboard(Piece::new(color, PieceKind::Pawn));
+            let enemy_pawns =
+                board.get_piece_bitboard(Piece::new(color.opposite(), PieceKind::Pawn));
+            let passed = passed_pawns(pawns, enemy_pawns, color);
+
+            let mut score = DOUBLED_PENALTY * doubled_pawns(pawns, color).count_ones() as i32
+                + ISOLATED_PENALTY * isolated_pawns(pawns).count_ones() as i32
+                + BACKWARD_PENALTY * backward_pawns(pawns, enemy_pawns, color).count_ones() as i32;
+            for square in squares(connected_pawns(pawns, color)) {
+                let bonus = CONNECTED_BONUS[relative_rank(square, color)];
+                score += TaperedScore::new(bonus, bonus);
+            }
+            for square in squares(passed) {
+                score += PASSED_BONUS[relative_rank(square, color)];
+            }
+
+            entry.score += score * color.get_value();
+            entry.passed |= passed;
+        }
+        entry
+    }
+
+    fn pack_score(&self) -> u64 {
+        self.score.mg as u32 as u64 | (self.score.eg as u32 as u64) << 32
+    }
+
+    fn unpack(score: u64, passed: Bitboard) -> Self {
+        PawnEntry {
+            score: TaperedScore::new(score as u32 as i32, (score >> 32) as u32 as i32),
+            passed,
+        }
+    }
+}
+
+/// Lock-free cache of pawn structures, shared by the search threads through the evaluator. Like
+/// in the transposition table, slots store their key xored with their data, so that a slot torn
+/// by concurrent writes doesn't match its key. An empty slot matches the position without pawns,
+/// whose entry is empty as well.
+struct PawnCache {
+    slots: Box<[PawnSlot]>,
+}
+
+#[derive(Default)]
+struct PawnSlot {
+    key: AtomicU64,
+    score: AtomicU64,
+    passed: AtomicU64,
+}
+
+impl PawnCache {
+    fn new() -> Self {
+        Self {
+            slots: (0..PAWN_CACHE_SIZE).map(|_| PawnSlot::default()).collect(),
+        }
+    }
+
+    fn get_slot(&self, key: u64) -> &PawnSlot {
+        &self.slots[key as usize % self.slots.len()]
+    }
+
+    fn probe(&self, key: u64) -> Option<PawnEntry> {
+        let slot = self.get_slot(key);
+        let score = slot.score.load(Ordering::Relaxed);
+        let passed = slot.passed.load(Ordering::Relaxed);
+        (slot.key.load(Ordering::Relaxed) ^ score ^ passed == key)
+            .then(|| PawnEntry::unpack(score, passed))
+    }
+
+    fn store(&self, key: u64, entry: &PawnEntry) {
+        let slot = self.get_slot(key);
+        let score = entry.pack_score();
+        slot.key
+            .store(key ^ score ^ entry.passed, Ordering::Relaxed);
+        slot.score.store(score, Ordering::Relaxed);
+        slot.passed.store(entry.passed, Ordering::Relaxed);
+    }
+}
+
+/// Endgame score for White of the passed pawns among `passed`, from how free their path is and
+/// where the kings stand.
+fn passed_pawn_endgame_score(board: &Board, passed: Bitboard) -> i32 {
+    let mut score = 0;
+    for color in Color::ALL {
+        let pawns = board.get_piece_bitboard(Piece::new(color, PieceKind::Pawn));
+        for square in squares(passed & pawns) {
+            let factor = PASSED_RANK_FACTOR[relative_rank(square, color)];
+            if factor == 0 {
+                continue;
+            }
+            let mut bonus = 0;
+            if front_span(square, color) & board.get_occupancy() == EMPTY {
+                bonus += FREE_PATH_BONUS;
+            }
+            if let Some(stop_square) = stop_square(square, color)
+                && let Some(own_king) = board.get_king_square(color)
+                && let Some(enemy_king) = board.get_king_square(color.opposite())
+            {
+                bonus += ENEMY_KING_DISTANCE_BONUS * distance(enemy_king, stop_square)
+                    - OWN_KING_DISTANCE_PENALTY * distance(own_king, stop_square);
+            }
+            score += bonus * factor * color.get_value();
+        }
+    }
+    score
+}
+
+/// Pawns of `color` with another one of theirs in front of them on their file.
+fn doubled_pawns(pawns: Bitboard, color: Color) -> Bitboard {
+    squares(pawns)
+        .filter(|&square| front_span(square, color) & pawns != EMPTY)
+        .fold(EMPTY, |doubled, square| doubled | 1 << square.to_index())
+}
+
+/// Pawns without pawns of their color on the adjacent files.
+fn isolated_pawns(pawns: Bitboard) -> Bitboard {
+    squares(pawns)
+        .filter(|square| adjacent_files(square.file) & pawns == EMPTY)
+        .fold(EMPTY, |isolated, square| isolated | 1 << square.to_index())
+}
+
+/// Pawns of `color` that are ahead of all the pawns of their color on the adjacent files, so
+/// that none can come to defend them, and that can't advance as an enemy pawn controls the
+/// square in front of them. Isolated pawns aren't counted again.
+fn backward_pawns(pawns: Bitboard, enemy_pawns: Bitboard, color: Color) -> Bitboard {
+    squares(pawns)
+        .filter(|&square| {
+            let neighbours = adjacent_files(square.file) & pawns;
+            neighbours != EMPTY
+                && neighbours & !ranks_ahead(square.rank, color) == EMPTY
+                && stop_square(square, color)
+                    .is_some_and(|stop| pawn_attacks(stop, color) & enemy_pawns != EMPTY)
+        })
+        .fold(EMPTY, |backward, square| backward | 1 << square.to_index())
+}
+
+/// Pawns of `color` defended by a pawn of their color, or standing next to one.
+fn connected_pawns(pawns: Bitboard, color: Color) -> Bitboard {
+    squares(pawns)
+        .filter(|&square| {
+            let rank = RANK_1 << (8 * square.rank);
+            pawn_attacks(square, color.opposite()) & pawns != EMPTY
+                || adjacent_files(square.file) & rank & pawns != EMPTY
+        })
+        .fold(EMPTY, |connected, square| {
+            connected | 1 << square.to_index()
+        })
+}
+
+/// Pawns of `color` that no enemy pawn can stop, as none stands in front of them on their file
+/// or the adjacent ones.
+fn passed_pawns(pawns: Bitboard, enemy_pawns: Bitboard, color: Color) -> Bitboard {
+    squares(pawns)
+        .filter(|&square| {
+            let files = FILE_A << square.file | adjacent_files(square.file);
+            files & ranks_ahead(square.rank, color) & enemy_pawns == EMPTY
+        })
+        .fold(EMPTY, |passed, square| passed | 1 << square.to_index())
+}
+
+pub(super) fn adjacent_files(file: u8) -> Bitboard {
+    let file = FILE_A << file;
+    (file << 1 & !FILE_A) | (file >> 1 & !(FILE_A << 7))
+}
+
+/// Squares on the ranks in front of `rank`, from the point of view of `color`.
+pub(super) fn ranks_ahead(rank: u8, color: Color) -> Bitboard {
+    match color {
+        Color::White if rank == 7 => EMPTY,
+        Color::White => !EMPTY << (8 * (rank + 1)),
+        Color::Black => (1 << (8 * rank)) - 1,
+    }
+}
+
+/// Squares in front of `square` on its file, from the point of view of `color`.
+fn front_span(square: Square, color: Color) -> Bitboard {
+    FILE_A << square.file & ranks_ahead(square.rank, color)
+}
+
+fn stop_square(square: Square, color: Color) -> Option<Square> {
+    square.offset(0, color.get_value() as i8)
+}
+
+/// Rank of `square` counted from the side of `color`, 0 being its back rank.
+pub(super) fn relative_rank(square: Square, color: Color) -> usize {
+    match color {
+        Color::White => square.rank as usize,
+        Color::Black => 7 - square.rank as usize,
+    }
+}
+
+/// Number of king moves between two squares.
+fn distance(from: Square, to: Square) -> i32 {
+    (from.file as i32 - to.file as i32)
+        .abs()
+        .max((from.rank as i32 - to.rank as i32).abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pawns(board: &Board, color: Color) -> Bitboard {
+        board.get_piece_bitboard(Piece::new(color, PieceKind::Pawn))
+    }
+
+    fn to_squares(bitboard: Bitboard) -> Vec<String> {
+        squares(bitboard).map(|square| square.to_string()).collect()
+    }
+
+    #[test]
+    fn test_pawn_features() {
+        let board = Board::from_fen("4k3/p4p2/1p4pP/1P1p4/3P4/2P5/P3P3/4K3 w - - 0 1");
+        let white = pawns(&board, Color::White);
+        let black = pawns(&board, Color::Black);
+
+        assert_eq!(
+            to_squares(doubled_pawns(white, Color::White)),
+            Vec::<String>::new()
+        );
+        assert_eq!(to_squares(isolated_pawns(white)), ["h6"]);
+        assert_eq!(to_squares(isolated_pawns(black)), ["d5"]);
+        assert_eq!(to_squares(connected_pawns(white, Color::White)), ["d4"]);
+        assert_eq!(
+            to_squares(connected_pawns(black, Color::Black)),
+            ["b6", "g6"]
+        );
+        assert_eq!(to_squares(passed_pawns(white, black, Color::White)), ["h6"]);
+        assert_eq!(to_squares(passed_pawns(black, white, Color::Black)), ["g6"]);
+        // c4 is controlled by d5, and a6 by b5, while their neighbours are already ahead
+        assert_eq!(
+            to_squares(backward_pawns(white, black, Color::White)),
+            ["c3"]
+        );
+        assert_eq!(
+            to_squares(backward_pawns(black, white, Color::Black)),
+            ["a7"]
+        );
+
+        let board = Board::from_fen("4k3/8/8/2p5/8/1P6/1P6/4K3 w - - 0 1");
+        assert_eq!(
+            to_squares(doubled_pawns(pawns(&board, Color::White), Color::White)),
+            ["b2"]
+        );
+
+        // d3 can't advance to d4, controlled by c5, and e4 can't defend it
+        let board = Board::from_fen("4k3/8/8/2p5/4P3/3P4/8/4K3 w - - 0 1");
+        let white = pawns(&board, Color::White);
+        let black = pawns(&board, Color::Black);
+        assert_eq!(
+            to_squares(backward_pawns(white, black, Color::White)),
+            ["d3"]
+        );
+    }
+
+    #[test]
+    fn test_passed_pawns_in_endgame() {
+        let evaluator = PawnStructureEvaluator::new(1);
+        // the same passed pawn is worth more with its king escorting it than with the enemy
+        // king in front of it
+        let escorted = Board::from_fen("8/8/1k6/8/3KP3/8/8/8 w - - 0 1");
+        let blocked = Board::from_fen("8/8/8/4k3/4P3/8/8/3K4 w - - 0 1");
+        assert!(evaluator.evaluate(&escorted) > evaluator.evaluate(&blocked));
+        assert!(evaluator.evaluate(&escorted) > 0);
+        // scores are relative to the side to move
+        let black_to_move = Board::from_fen("8/8/1k6/8/3KP3/8/8/8 b - - 0 1");
+        assert_eq!(
+            evaluator.evaluate(&black_to_move),
+            -evaluator.evaluate(&escorted)
+        );
+    }
+
+    #[test]
+    fn test_cache() {
+        let evaluator = PawnStructureEvaluator::new(1);
+        let board = Board::from_fen("4k3/p4p2/1p4p1/1P1p4/3P3P/2P5/P3P3/4K3 w - - 0 1");
+        let entry = PawnEntry::compute(&board);
+        assert_eq!(evaluator.cache.probe(board.pawn_hash()), None);
+        assert_eq!(evaluator.get_entry(&board), entry);
+        assert_eq!(evaluator.cache.probe(board.pawn_hash()), Some(entry));
+        // pawns only: a different piece placement shares the entry
+        let moved_king = Board::from_fen("3k4/p4p2/1p4p1/1P1p4/3P3P/2P5/P3P3/4K3 w - - 0 1");
+        assert_eq!(moved_king.pawn_hash(), board.pawn_hash());
+        assert_eq!(evaluator.get_entry(&moved_king), entry);
+    }
+}
@@ -0,0 +1,373 @@
+use super::pawn_structure::{adjacent_files, ranks_ahead, relative_rank};
+use crate::Board;
+use crate::attacks::pawn_attacks;
+use crate::board::bitboard::{Bitboard, EMPTY, FILE_A, LIGHT_SQUARES, RANK_1, squares};
+use crate::board::piece::{Color, Piece, PieceKind};
+use crate::board::square::Square;
+use crate::evaluate::{Evaluator, TaperedScore, game_phase};
+
+/// Terms of [`PiecesEvaluator`], each with its own weight and its own line in the breakdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PieceTerm {
+    /// Bishops on both square colors.
+    BishopPair,
+    /// Rooks on files without pawns, or without pawns of their color.
+    RookOpenFile,
+    /// Rooks on the seventh rank, holding enemy pawns or the enemy king on the eighth.
+    RookSeventhRank,
+    /// Knights and bishops on the enemy half of the board, defended by a pawn and out of reach
+    /// of the enemy pawns.
+    Outpost,
+    /// Bishops shut in behind enemy pawns, and rooks shut in by their own king.
+    TrappedPiece,
+    /// Queens moved out while knights and bishops still stand on their starting squares.
+    EarlyQueen,
+}
+
+impl PieceTerm {
+    pub const ALL: [PieceTerm; 6] = [
+        PieceTerm::BishopPair,
+        PieceTerm::RookOpenFile,
+        PieceTerm::RookSeventhRank,
+        PieceTerm::Outpost,
+        PieceTerm::TrappedPiece,
+        PieceTerm::EarlyQueen,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            PieceTerm::BishopPair => "bishop pair",
+            PieceTerm::RookOpenFile => "rook open file",
+            PieceTerm::RookSeventhRank => "rook 7th rank",
+            PieceTerm::Outpost => "outpost",
+            PieceTerm::TrappedPiece => "trapped piece",
+            PieceTerm::EarlyQueen => "early queen",
+        }
+    }
+
+    /// Score for White in centipawns, before weighting.
+    fn evaluate(&self, board: &Board) -> TaperedScore {
+        Color::ALL
+            .into_iter()
+            .map(|color| {
+                let score = match self {
+                    PieceTerm::BishopPair => bishop_pair(board, color),
+                    PieceTerm::RookOpenFile => rook_open_file(board, color),
+                    PieceTerm::RookSeventhRank => rook_seventh_rank(board, color),
+                    PieceTerm::Outpost => outpost(board, color),
+                    PieceTerm::TrappedPiece => trapped_piece(board, color),
+                    PieceTerm::EarlyQueen => early_queen(board, color),
+                };
+                score * color.get_value()
+            })
+            .sum()
+    }
+}
+
+/// Scores in centipawns.
+const BISHOP_PAIR_BONUS: TaperedScore = TaperedScore::new(30, 50);
+const ROOK_OPEN_FILE_BONUS: TaperedScore = TaperedScore::new(25, 10);
+const ROOK_SEMI_OPEN_FILE_BONUS: TaperedScore = TaperedScore::new(12, 5);
+const ROOK_SEVENTH_RANK_BONUS: TaperedScore = TaperedScore::new(20, 35);
+const KNIGHT_OUTPOST_BONUS: TaperedScore = TaperedScore::new(25, 15);
+const BISHOP_OUTPOST_BONUS: TaperedScore = TaperedScore::new(12, 6);
+const TRAPPED_BISHOP_PENALTY: TaperedScore = TaperedScore::new(-80, -80);
+const TRAPPED_ROOK_PENALTY: TaperedScore = TaperedScore::new(-40, -10);
+/// For each undeveloped knight or bishop.
+const EARLY_QUEEN_PENALTY: TaperedScore = TaperedScore::new(-10, 0);
+
+/// Scores how the pieces stand with regard to each other and the pawns. Each [`PieceTerm`] is
+/// weighted separately, all of them with the weight given to [`PiecesEvaluator::new`] unless
+/// changed with [`PiecesEvaluator::with_term_weight`].
+pub struct PiecesEvaluator {
+    weights: [i32; PieceTerm::ALL.len()],
+}
+
+impl PiecesEvaluator {
+    pub fn new(weight: i32) -> Self {
+        Self {
+            weights: [weight; PieceTerm::ALL.len()],
+        }
+    }
+
+    pub fn with_term_weight(mut self, term: PieceTerm, weight: i32) -> Self {
+        self.weights[term as usize] = weight;
+        self
+    }
+
+    /// Score of `term` relative to the side to move, weighted.
+    fn evaluate_term(&self, board: &Board, term: PieceTerm) -> TaperedScore {
+        let weight = self.weights[term as usize];
+        if weight == 0 {
+            return TaperedScore::default();
+        }
+        term.evaluate(board) * (board.get_active_color().get_value() * weight)
+    }
+}
+
+impl Evaluator for PiecesEvaluator {
+    fn name(&self) -> String {
+        "pieces".to_string()
+    }
+    fn evaluate(&self, board: &Board) -> i32 {
+        self.evaluate_tapered(board).taper(game_phase(board))
+    }
+    fn evaluate_tapered(&self, board: &Board) -> TaperedScore {
+        PieceTerm::ALL
+            .iter()
+            .map(|&term| self.evaluate_term(board, term))
+            .sum()
+    }
+    fn evaluate_breakdown(&self, board: &Board) -> Vec<(String, TaperedScore)> {
+        PieceTerm::ALL
+            .iter()
+            .map(|&term| (term.get_name().to_string(), self.evaluate_term(board, term)))
+            .collect()
+    }
+}
+
+fn pieces(board: &Board, color: Color, kind: PieceKind) -> Bitboard {
+    board.get_piece_bitboard(Piece::new(color, kind))
+}
+
+/// Squares of the rank counted from the side of `color`, 0 being its back rank.
+fn relative_rank_mask(rank: usize, color: Color) -> Bitboard {
+    match color {
+        Color::White => RANK_1 << (8 * rank),
+        Color::Black => RANK_1 << (8 * (7 - rank)),
+    }
+}
+
+fn bishop_pair(board: &Board, color: Color) -> TaperedScore {
+    let bishops = pieces(board, color, PieceKind::Bishop);
+    if bishops & LIGHT_SQUARES != EMPTY && bishops & !LIGHT_SQUARES != EMPTY {
+        BISHOP_PAIR_BONUS
+    } else {
+        TaperedScore::default()
+    }
+}
+
+fn rook_open_file(board: &Board, color: Color) -> TaperedScore {
+    let pawns = pieces(board, color, PieceKind::Pawn);
+    let enemy_pawns = pieces(board, color.opposite(), PieceKind::Pawn);
+    squares(pieces(board, color, PieceKind::Rook))
+        .map(|square| {
+            let file = FILE_A << square.file;
+            if file & (pawns | enemy_pawns) == EMPTY {
+                ROOK_OPEN_FILE_BONUS
+            } else if file & pawns == EMPTY {
+                ROOK_SEMI_OPEN_FILE_BONUS
+            } else {
+                TaperedScore::default()
+            }
+        })
+        .sum()
+}
+
+fn rook_seventh_rank(board: &Board, color: Color) -> TaperedScore {
+    let seventh = relative_rank_mask(6, color);
+    let eighth = relative_rank_mask(7, color);
+    let enemy_pawns = pieces(board, color.opposite(), PieceKind::Pawn);
+    let enemy_king = pieces(board, color.opposite(), PieceKind::King);
+    if seventh & enemy_pawns == EMPTY && eighth & enemy_king == EMPTY {
+        return TaperedScore::default();
+    }
+    ROOK_SEVENTH_RANK_BONUS * (pieces(board, color, PieceKind::Rook) & seventh).count_ones() as i32
+}
+
+fn outpost(board: &Board, color: Color) -> TaperedScore {
+    let pawns = pieces(board, color, PieceKind::Pawn);
+    let enemy_pawns = pieces(board, color.opposite(), PieceKind::Pawn);
+    let is_outpost = |square: Square| {
+        (3..=5).contains(&relative_rank(square, color))
+            && pawn_attacks(square, color.opposite()) & pawns != EMPTY
+            && adjacent_files(square.file) & ranks_ahead(square.rank, color) & enemy_pawns == EMPTY
+    };
+    let count = |kind| {
+        squares(pieces(board, color, kind))
+            .filter(|&square| is_outpost(square))
+            .count() as i32
+    };
+    KNIGHT_OUTPOST_BONUS * count(PieceKind::Knight)
+        + BISHOP_OUTPOST_BONUS * count(PieceKind::Bishop)
+}
+
+fn trapped_piece(board: &Board, color: Color) -> TaperedScore {
+    let mut score = TaperedScore::default();
+    // A bishop taking a pawn in the corner can be cut off by the pawn next to it
+    let enemy_pawns = pieces(board, color.opposite(), PieceKind::Pawn);
+    for square in squares(pieces(board, color, PieceKind::Bishop)) {
+        if relative_rank(square, color) != 6 || !matches!(square.file, 0 | 7) {
+            continue;
+        }
+        let file_towards_centre = if square.file == 0 { 1 } else { 6 };
+        let blocker = FILE_A << file_towards_centre & relative_rank_mask(5, color);
+        if blocker & enemy_pawns != EMPTY {
+            score += TRAPPED_BISHOP_PENALTY;
+        }
+    }
+    // A rook in the corner behind a king that walked towards it instead of castling
+    let back_rank = relative_rank_mask(0, color);
+    if let Some(king) = board.get_king_square(color)
+        && relative_rank(king, color) == 0
+    {
+        let corner_files = match king.file {
+            1..=3 => 0..king.file,
+            5 | 6 => king.file + 1..8,
+            _ => 0..0,
+        };
+        let corner = corner_files.fold(EMPTY, |corner, file| corner | FILE_A << file);
+        let rooks = pieces(board, color, PieceKind::Rook) & back_rank & corner;
+        score += TRAPPED_ROOK_PENALTY * rooks.count_ones() as i32;
+    }
+    score
+}
+
+fn early_queen(board: &Board, color: Color) -> TaperedScore {
+    let back_rank = relative_rank_mask(0, color);
+    let queens = pieces(board, color, PieceKind::Queen);
+    let queen_start = FILE_A << 3 & back_rank;
+    if queens == EMPTY || queens & queen_start != EMPTY {
+        return TaperedScore::default();
+    }
+    let knights_start = (FILE_A << 1 | FILE_A << 6) & back_rank;
+    let bishops_start = (FILE_A << 2 | FILE_A << 5) & back_rank;
+    let undeveloped = (pieces(board, color, PieceKind::Knight) & knights_start).count_ones()
+        + (pieces(board, color, PieceKind::Bishop) & bishops_start).count_ones();
+    EARLY_QUEEN_PENALTY * undeveloped as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(fen: &str, term: PieceTerm) -> TaperedScore {
+        term.evaluate(&Board::from_fen(fen))
+    }
+
+    #[test]
+    fn test_terms() {
+        assert_eq!(
+            term("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1", PieceTerm::BishopPair),
+            BISHOP_PAIR_BONUS
+        );
+        // both bishops on dark squares after a promotion
+        assert_eq!(
+            term("4k3/8/8/8/8/B7/8/2B1K3 w - - 0 1", PieceTerm::BishopPair),
+            TaperedScore::default()
+        );
+        assert_eq!(
+            term(
+                "3rk3/3p4/8/8/8/8/p7/R3K2R w - - 0 1",
+                PieceTerm::RookOpenFile
+            ),
+            ROOK_SEMI_OPEN_FILE_BONUS + ROOK_OPEN_FILE_BONUS
+        );
+        assert_eq!(
+            term(
+                "6k1/R4ppp/8/8/8/8/8/6K1 w - - 0 1",
+                PieceTerm::RookSeventhRank
+            ),
+            ROOK_SEVENTH_RANK_BONUS
+        );
+        assert_eq!(
+            term("4k3/R7/8/8/8/8/8/6K1 w - - 0 1", PieceTerm::RookSeventhRank),
+            ROOK_SEVENTH_RANK_BONUS
+        );
+        assert_eq!(
+            term("4k3/8/8/8/8/8/r7/6K1 w - - 0 1", PieceTerm::RookSeventhRank),
+            -ROOK_SEVENTH_RANK_BONUS
+        );
+        assert_eq!(
+            term("8/R7/3k4/8/8/8/8/6K1 w - - 0 1", PieceTerm::RookSeventhRank),
+            TaperedScore::default()
+        );
+    }
+
+    #[test]
+    fn test_outposts() {
+        // e5 is defended by d4 and no black pawn can chase the knight from it
+        assert_eq!(
+            term("4k3/pp4p1/8/4N3/3P4/8/8/4K3 w - - 0 1", PieceTerm::Outpost),
+            KNIGHT_OUTPOST_BONUS
+        );
+        // f7-f6 can
+        assert_eq!(
+            term("4k3/pp3p2/8/4N3/3P4/8/8/4K3 w - - 0 1", PieceTerm::Outpost),
+            TaperedScore::default()
+        );
+        // not defended by a pawn
+        assert_eq!(
+            term("4k3/pp3p2/8/8/3Pn3/8/8/4K3 w - - 0 1", PieceTerm::Outpost),
+            TaperedScore::default()
+        );
+        // black bishop on d4, defended by e5 and out of reach of the white pawns
+        assert_eq!(
+            term("4k3/8/8/4p3/3b4/8/PP4PP/4K3 w - - 0 1", PieceTerm::Outpost),
+            -BISHOP_OUTPOST_BONUS
+        );
+    }
+
+    #[test]
+    fn test_trapped_pieces() {
+        assert_eq!(
+            term("4k3/B7/1pp5/8/8/8/8/4K3 w - - 0 1", PieceTerm::TrappedPiece),
+            TRAPPED_BISHOP_PENALTY
+        );
+        assert_eq!(
+            term("4k3/B7/2p5/8/8/8/8/4K3 w - - 0 1", PieceTerm::TrappedPiece),
+            TaperedScore::default()
+        );
+        assert_eq!(
+            term("r3k2r/8/8/8/8/8/8/R4K1R w - - 0 1", PieceTerm::TrappedPiece),
+            TRAPPED_ROOK_PENALTY
+        );
+        // castled: the rook already left the corner
+        assert_eq!(
+            term("r3k2r/8/8/8/8/8/8/R4RK1 w - - 0 1", PieceTerm::TrappedPiece),
+            TaperedScore::default()
+        );
+        assert_eq!(
+            term("rk5r/8/8/8/8/8/8/R3K2R w - - 0 1", PieceTerm::TrappedPiece),
+            -TRAPPED_ROOK_PENALTY
+        );
+    }
+
+    #[test]
+    fn test_early_queen() {
+        // 1. e4 e5 2. Qh5
+        assert_eq!(
+            term(
+                "rnbqkbnr/pppp1ppp/8/4p2Q/4P3/8/PPPP1PPP/RNB1KBNR b KQkq - 1 2",
+                PieceTerm::EarlyQueen
+            ),
+            EARLY_QUEEN_PENALTY * 4
+        );
+        assert_eq!(
+            term(
+                "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2",
+                PieceTerm::EarlyQueen
+            ),
+            TaperedScore::default()
+        );
+    }
+
+    #[test]
+    fn test_breakdown() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/2B1KB2 b - - 0 1");
+        let evaluator = PiecesEvaluator::new(10).with_term_weight(PieceTerm::Outpost, 0);
+        let breakdown = evaluator.evaluate_breakdown(&board);
+        assert_eq!(
+            breakdown
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>(),
+            PieceTerm::ALL.map(|term| term.get_name())
+        );
+        assert_eq!(breakdown[0].1, -BISHOP_PAIR_BONUS * 10);
+        assert_eq!(
+            evaluator.evaluate_tapered(&board),
+            breakdown.iter().map(|(_, score)| *score).sum()
+        );
+    }
+}
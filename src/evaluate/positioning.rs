@@ -1,7 +1,7 @@
 use crate::Board;
-use crate::board::piece::{Color, PieceKind};
-use crate::board::square::Square;
-use crate::evaluate::Evaluator;
+use crate::board::bitboard::squares;
+use crate::board::piece::{Color, Piece, PieceKind};
+use crate::evaluate::{Evaluator, TaperedScore, game_phase};
 
 pub struct PositioningEvaluator {
     weight: i32,
@@ -18,31 +18,35 @@ impl Evaluator for PositioningEvaluator {
         "positioning".to_string()
     }
     fn evaluate(&self, board: &Board) -> i32 {
-        let mut score = 0;
-        for idx in 0..64 {
-            let square = Square::from_index(idx).unwrap();
-            if let Some(piece) = board.get_piece(square) {
-                let color_aware_rank = if piece.get_color() == Color::White {
-                    7 - square.rank as usize
-                } else {
-                    square.rank as usize
-                };
-                let piece_value = match piece.get_kind() {
-                    PieceKind::Pawn => PAWN_TABLE[color_aware_rank][square.file as usize],
-                    PieceKind::Knight => KNIGHT_TABLE[color_aware_rank][square.file as usize],
-                    PieceKind::Bishop => BISHOP_TABLE[color_aware_rank][square.file as usize],
-                    PieceKind::Rook => ROOK_TABLE[color_aware_rank][square.file as usize],
-                    PieceKind::Queen => QUEEN_TABLE[color_aware_rank][square.file as usize],
-                    PieceKind::King => KING_TABLE[color_aware_rank][square.file as usize],
+        self.evaluate_tapered(board).taper(game_phase(board))
+    }
+    fn evaluate_tapered(&self, board: &Board) -> TaperedScore {
+        let mut score = TaperedScore::default();
+        for color in Color::ALL {
+            for kind in PieceKind::ALL {
+                let (mg_table, eg_table) = match kind {
+                    PieceKind::Pawn => (&PAWN_MG_TABLE, &PAWN_EG_TABLE),
+                    PieceKind::Knight => (&KNIGHT_TABLE, &KNIGHT_TABLE),
+                    PieceKind::Bishop => (&BISHOP_TABLE, &BISHOP_TABLE),
+                    PieceKind::Rook => (&ROOK_TABLE, &ROOK_TABLE),
+                    PieceKind::Queen => (&QUEEN_TABLE, &QUEEN_TABLE),
+                    PieceKind::King => (&KING_MG_TABLE, &KING_EG_TABLE),
                 };
-                if piece.get_color() == Color::White {
-                    score += piece_value;
-                } else {
-                    score -= piece_value;
+                for square in squares(board.get_piece_bitboard(Piece::new(color, kind))) {
+                    let color_aware_rank = if color == Color::White {
+                        7 - square.rank as usize
+                    } else {
+                        square.rank as usize
+                    };
+                    let file = square.file as usize;
+                    score += TaperedScore::new(
+                        mg_table[color_aware_rank][file],
+                        eg_table[color_aware_rank][file],
+                    ) * color.get_value();
                 }
             }
         }
-        score * board.get_active_color().get_value() * self.weight
+        score * (board.get_active_color().get_value() * self.weight)
     }
 }
 
@@ -50,7 +54,7 @@ impl Evaluator for PositioningEvaluator {
 type PieceSquareTable = [[i32; 8]; 8];
 
 #[rustfmt::skip]
-const PAWN_TABLE: PieceSquareTable = [
+const PAWN_MG_TABLE: PieceSquareTable = [
     [0,  0,  0,  0,  0,  0,  0,  0],
     [50, 50, 50, 50, 50, 50, 50, 50],
     [10, 10, 20, 30, 30, 20, 10, 10],
@@ -61,6 +65,19 @@ const PAWN_TABLE: PieceSquareTable = [
     [0,  0,  0,  0,  0,  0,  0,  0],
 ];
 
+// Passed or not, a pawn gets more dangerous the closer it is to promotion
+#[rustfmt::skip]
+const PAWN_EG_TABLE: PieceSquareTable = [
+    [0,  0,  0,  0,  0,  0,  0,  0],
+    [80, 80, 80, 80, 80, 80, 80, 80],
+    [50, 50, 50, 50, 50, 50, 50, 50],
+    [30, 30, 30, 30, 30, 30, 30, 30],
+    [15, 15, 15, 15, 15, 15, 15, 15],
+    [5,  5,  5,  5,  5,  5,  5,  5],
+    [0,  0,  0,  0,  0,  0,  0,  0],
+    [0,  0,  0,  0,  0,  0,  0,  0],
+];
+
 #[rustfmt::skip]
 const KNIGHT_TABLE: PieceSquareTable = [
     [-50,-40,-30,-30,-30,-30,-40,-50],
@@ -110,7 +127,7 @@ const QUEEN_TABLE: PieceSquareTable = [
 ];
 
 #[rustfmt::skip]
-const KING_TABLE: PieceSquareTable = [
+const KING_MG_TABLE: PieceSquareTable = [
     [-30,-40,-40,-50,-50,-40,-40,-30],
     [-30,-40,-40,-50,-50,-40,-40,-30],
     [-30,-40,-40,-50,-50,-40,-40,-30],
@@ -120,3 +137,47 @@ const KING_TABLE: PieceSquareTable = [
     [20, 20,  0,  0,  0,  0, 20, 20],
     [20, 30, 10,  0,  0, 10, 30, 20],
 ];
+
+// Without queens and rooks to attack it, the king is an active piece and belongs in the center
+#[rustfmt::skip]
+const KING_EG_TABLE: PieceSquareTable = [
+    [-50,-40,-30,-20,-20,-30,-40,-50],
+    [-30,-20,-10,  0,  0,-10,-20,-30],
+    [-30,-10, 20, 30, 30, 20,-10,-30],
+    [-30,-10, 30, 40, 40, 30,-10,-30],
+    [-30,-10, 30, 40, 40, 30,-10,-30],
+    [-30,-10, 20, 30, 30, 20,-10,-30],
+    [-30,-30,  0,  0,  0,  0,-30,-30],
+    [-50,-30,-30,-30,-30,-30,-30,-50],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(fen: &str) -> i32 {
+        PositioningEvaluator::new(1).evaluate(&Board::from_fen(fen))
+    }
+
+    #[test]
+    fn test_king_placement_depends_on_phase() {
+        // with most pieces on the board the king is better at home than in the center
+        assert!(
+            evaluate("rn1qkbnr/8/8/8/4K3/8/8/RN1Q1BNR w - - 0 1")
+                < evaluate("rn1qkbnr/8/8/8/8/8/8/RN1QKBNR w - - 0 1")
+        );
+        // in a pawn ending it belongs in the center
+        assert!(
+            evaluate("4k3/4p3/8/8/4K3/8/4P3/8 w - - 0 1")
+                > evaluate("4k3/4p3/8/8/8/8/4P3/6K1 w - - 0 1")
+        );
+    }
+
+    #[test]
+    fn test_pawns_advance_in_endgame() {
+        let board = Board::from_fen("4k3/8/1P6/8/8/8/8/4K3 w - - 0 1");
+        let score = PositioningEvaluator::new(1).evaluate_tapered(&board);
+        assert!(score.eg > score.mg);
+        assert_eq!(PositioningEvaluator::new(1).evaluate(&board), score.eg);
+    }
+}
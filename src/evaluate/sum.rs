@@ -1,4 +1,5 @@
-use crate::evaluate::Evaluator;
+use crate::board::piece::PieceKind;
+use crate::evaluate::{Evaluator, TaperedScore};
 
 pub struct SumEvaluator {
     evaluators: Vec<Box<dyn Evaluator>>,
@@ -8,13 +9,6 @@ impl SumEvaluator {
     pub fn new(evaluators: Vec<Box<dyn Evaluator>>) -> Self {
         Self { evaluators }
     }
-
-    pub fn evaluate_breakdown(&self, board: &crate::Board) -> Vec<(String, i32)> {
-        self.evaluators
-            .iter()
-            .map(|e| (e.name(), e.evaluate(board)))
-            .collect()
-    }
 }
 
 impl Evaluator for SumEvaluator {
@@ -35,4 +29,26 @@ impl Evaluator for SumEvaluator {
             .map(|evaluator| evaluator.evaluate(board))
             .sum()
     }
+
+    fn evaluate_tapered(&self, board: &crate::Board) -> TaperedScore {
+        self.evaluators
+            .iter()
+            .map(|evaluator| evaluator.evaluate_tapered(board))
+            .sum()
+    }
+
+    /// Terms of each evaluator, relative to the side to move.
+    fn evaluate_breakdown(&self, board: &crate::Board) -> Vec<(String, TaperedScore)> {
+        self.evaluators
+            .iter()
+            .flat_map(|evaluator| evaluator.evaluate_breakdown(board))
+            .collect()
+    }
+
+    fn piece_value(&self, kind: PieceKind) -> i32 {
+        self.evaluators
+            .iter()
+            .map(|evaluator| evaluator.piece_value(kind))
+            .sum()
+    }
 }
@@ -0,0 +1,130 @@
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+use crate::Board;
+use crate::board::piece::{Color, Piece, PieceKind};
+
+/// Game phase of a position that still has all the pieces of the starting position.
+pub const MAX_PHASE: i32 = 24;
+
+/// Game phase of `board` from the remaining non-pawn material, going from [`MAX_PHASE`] with all
+/// the pieces down to 0 with only kings and pawns left. Knights and bishops count for 1, rooks
+/// for 2 and queens for 4, promotions can't push it past the maximum.
+pub fn game_phase(board: &Board) -> i32 {
+    let mut phase = 0;
+    for color in Color::ALL {
+        for (kind, weight) in [
+            (PieceKind::Knight, 1),
+            (PieceKind::Bishop, 1),
+            (PieceKind::Rook, 2),
+            (PieceKind::Queen, 4),
+        ] {
+            phase += weight
+                * board
+                    .get_piece_bitboard(Piece::new(color, kind))
+                    .count_ones() as i32;
+        }
+    }
+    phase.min(MAX_PHASE)
+}
+
+/// Score with separate middlegame and endgame values, blended by the game phase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TaperedScore {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl TaperedScore {
+    pub const fn new(mg: i32, eg: i32) -> Self {
+        Self { mg, eg }
+    }
+
+    /// Interpolates between the two values: the middlegame one at [`MAX_PHASE`], the endgame one
+    /// at 0.
+    pub fn taper(&self, phase: i32) -> i32 {
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for TaperedScore {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.mg + other.mg, self.eg + other.eg)
+    }
+}
+
+impl AddAssign for TaperedScore {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for TaperedScore {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.mg - other.mg, self.eg - other.eg)
+    }
+}
+
+impl Neg for TaperedScore {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.mg, -self.eg)
+    }
+}
+
+impl Mul<i32> for TaperedScore {
+    type Output = Self;
+
+    fn mul(self, factor: i32) -> Self {
+        Self::new(self.mg * factor, self.eg * factor)
+    }
+}
+
+impl Sum for TaperedScore {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_phase() {
+        assert_eq!(game_phase(&Board::default()), MAX_PHASE);
+        assert_eq!(
+            game_phase(&Board::from_fen("4k3/pppp4/8/8/8/8/4PPPP/4K3 w - - 0 1")),
+            0
+        );
+        assert_eq!(
+            game_phase(&Board::from_fen("3qk3/8/8/8/8/8/8/1N2KR2 w - - 0 1")),
+            7
+        );
+        // extra queens from promotions don't go past the opening phase
+        assert_eq!(
+            game_phase(&Board::from_fen("4k3/8/8/8/8/8/QQQQ4/QQQ1K3 w - - 0 1")),
+            MAX_PHASE
+        );
+    }
+
+    #[test]
+    fn test_taper() {
+        let score = TaperedScore::new(100, -20);
+        assert_eq!(score.taper(MAX_PHASE), 100);
+        assert_eq!(score.taper(0), -20);
+        assert_eq!(score.taper(MAX_PHASE / 2), 40);
+        assert_eq!(
+            [score, TaperedScore::new(10, 30)]
+                .into_iter()
+                .sum::<TaperedScore>()
+                * 2,
+            TaperedScore::new(220, 20)
+        );
+    }
+}
@@ -1,3 +1,4 @@
+pub mod attacks;
 pub mod board;
 pub mod edp;
 pub mod evaluate;
@@ -1,44 +1,12 @@
+use crate::attacks::{
+    bishop_attacks, king_attacks, knight_attacks, pawn_attacks, queen_attacks, rook_attacks,
+};
 use crate::board::Board;
+use crate::board::bitboard::{Bitboard, EMPTY, square_bitboard, squares};
 use crate::board::piece::{Color, Piece, PieceKind};
 use crate::board::square::Square;
 use std::fmt::{Display, Formatter};
 
-const KNIGHT_OFFSETS: [(i8, i8); 8] = [
-    (-2, -1),
-    (-2, 1),
-    (-1, -2),
-    (-1, 2),
-    (1, -2),
-    (1, 2),
-    (2, -1),
-    (2, 1),
-];
-
-const WHITE_PAWN_ATTACK_OFFSETS: [(i8, i8); 2] = [(-1, 1), (1, 1)];
-const BLACK_PAWN_ATTACK_OFFSETS: [(i8, i8); 2] = [(-1, -1), (1, -1)];
-const BISHOP_OFFSETS: [(i8, i8); 4] = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
-const ROOK_OFFSETS: [(i8, i8); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
-const KING_OFFSETS: [(i8, i8); 8] = [
-    (-1, 0),
-    (-1, -1),
-    (1, 1),
-    (1, -1),
-    (-1, 1),
-    (1, 0),
-    (0, -1),
-    (0, 1),
-];
-const QUEEN_OFFSETS: [(i8, i8); 8] = [
-    (-1, -1),
-    (-1, 1),
-    (1, -1),
-    (1, 1),
-    (-1, 0),
-    (1, 0),
-    (0, -1),
-    (0, 1),
-];
-
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Move {
     pub from: Square,
@@ -63,15 +31,9 @@ impl Move {
         }
     }
 
+    /// Origin and destination squares, followed by the promotion piece, e.g. `e2e4` or `g7g8Q`.
     pub fn to_long_algebraic_notation(&self) -> String {
-        let mut notation = String::new();
-        notation.push_str(&format!(
-            "{}{}{}{}",
-            self.piece.to_ascii_char().to_ascii_uppercase(),
-            self.from,
-            if self.capture.is_some() { "x" } else { "" },
-            self.to
-        ));
+        let mut notation = format!("{}{}", self.from, self.to);
         if let Some(promotion_piece) = self.promotion {
             notation.push(promotion_piece.to_ascii_char().to_ascii_uppercase());
         }
@@ -100,9 +62,35 @@ impl Move {
         notation
     }
 
+    /// Short algebraic notation of a line of moves played from `board`.
+    pub fn line_to_short_algebraic_notation(board: &Board, line: &[Move]) -> String {
+        let mut board = board.clone();
+        let mut notations = Vec::with_capacity(line.len());
+        for m in line {
+            notations.push(m.to_short_algebraic_notation(&board));
+            board.apply_move(m);
+        }
+        notations.join(" ")
+    }
+
+    /// Move as UCI expects it: origin and destination squares followed by the lowercase
+    /// promotion piece, e.g. `e2e4`, `e7e8q`, or `e1g1` for castling.
     pub fn to_uci_notation(&self) -> String {
-        let mut long = self.to_long_algebraic_notation();
-        return long.drain(..4).collect();
+        let mut notation = format!("{}{}", self.from, self.to);
+        if let Some(promotion_piece) = self.promotion {
+            notation.push(promotion_piece.to_ascii_char().to_ascii_lowercase());
+        }
+        notation
+    }
+
+    /// Legal move of `board` written in UCI notation, `None` if the notation is invalid or the
+    /// move isn't legal.
+    pub fn from_uci_notation(board: &Board, notation: &str) -> Option<Move> {
+        let notation = notation.to_ascii_lowercase();
+        board
+            .get_legal_moves()
+            .into_iter()
+            .find(|m| m.to_uci_notation() == notation)
     }
 
     fn with_capture(&mut self, capture: Piece) -> Self {
@@ -131,57 +119,42 @@ impl Move {
     }
 }
 
-fn get_moves_in_line(
+fn add_moves_to_targets(
     board: &Board,
     square: Square,
     piece: Piece,
-    directions: Vec<(i8, i8)>,
-) -> Vec<Move> {
-    let mut moves: Vec<Move> = Vec::new();
-
-    for (file_delta, rank_delta) in directions.iter() {
-        let mut target_square_option = square.offset(*file_delta, *rank_delta);
-        while let Some(target_square) = target_square_option {
-            if let Some(other_piece) = board.get_piece(target_square) {
-                if other_piece.get_color() != piece.get_color() {
-                    moves.push(Move::new(square, target_square, piece).with_capture(other_piece));
-                }
-                break;
-            } else {
-                moves.push(Move::new(square, target_square, piece));
-            }
-            target_square_option = target_square.offset(*file_delta, *rank_delta);
-        }
+    targets: Bitboard,
+    moves: &mut Vec<Move>,
+) {
+    let own = board.get_color_bitboard(piece.get_color());
+    for target_square in squares(targets & !own) {
+        moves.push(
+            Move::new(square, target_square, piece)
+                .with_capture_option(board.get_piece(target_square)),
+        );
     }
-    moves
 }
 
-fn get_moves_at_offsets(
-    board: &Board,
-    square: Square,
-    piece: Piece,
-    offsets: Vec<(i8, i8)>,
-) -> Vec<Move> {
-    let mut moves: Vec<Move> = Vec::new();
-
-    for target_square in offsets
-        .iter()
-        .map(|(file_delta, rank_delta)| square.offset(*file_delta, *rank_delta))
-        .filter(|o| o.is_some())
-        .map(|o| o.unwrap())
-    {
-        let other_piece_option = board.get_piece(target_square);
-        if other_piece_option.is_none_or(|p| p.get_color() != piece.get_color()) {
+fn add_pawn_move(m: Move, moves: &mut Vec<Move>) {
+    if m.to.rank == 0 || m.to.rank == 7 {
+        for kind in [
+            PieceKind::Queen,
+            PieceKind::Rook,
+            PieceKind::Bishop,
+            PieceKind::Knight,
+        ] {
             moves.push(
-                Move::new(square, target_square, piece).with_capture_option(other_piece_option),
-            )
+                Move::new(m.from, m.to, m.piece)
+                    .with_capture_option(m.capture)
+                    .with_promotion(Piece::new(m.piece.get_color(), kind)),
+            );
         }
+    } else {
+        moves.push(m);
     }
-    moves
 }
 
-fn get_moves_for_pawn(board: &Board, square: Square, piece: Piece) -> Vec<Move> {
-    let mut moves = Vec::new();
+fn add_moves_for_pawn(board: &Board, square: Square, piece: Piece, moves: &mut Vec<Move>) {
     let dir = match piece.get_color() {
         Color::White => 1,
         Color::Black => -1,
@@ -190,7 +163,7 @@ fn get_moves_for_pawn(board: &Board, square: Square, piece: Piece) -> Vec<Move>
     if let Some(forward_square) = forward_square_option
         && board.is_square_empty(forward_square)
     {
-        moves.push(Move::new(square, forward_square, piece));
+        add_pawn_move(Move::new(square, forward_square, piece), moves);
 
         // double move
         if (dir < 0 && square.rank == 6) || (dir > 0 && square.rank == 1) {
@@ -203,58 +176,33 @@ fn get_moves_for_pawn(board: &Board, square: Square, piece: Piece) -> Vec<Move>
             }
         }
     }
-    let attack_offsets = [(-1, dir), (1, dir)];
-    for attack_square in attack_offsets
-        .iter()
-        .map(|(file_delta, rank_delta)| square.offset(*file_delta, *rank_delta))
-        .filter(|o| o.is_some())
-        .map(|o| o.unwrap())
-    {
-        if let Some(other_piece) = board.get_piece(attack_square)
-            && other_piece.get_color() != piece.get_color()
-        {
-            moves.push(Move::new(square, attack_square, piece).with_capture(other_piece));
-        }
 
-        // en passant
-        // if both capture and en_passant_square are set, the en_passant square points to captured pawn
-        if Some(attack_square) == board.get_game_state().en_passant_square {
-            moves.push(
-                Move::new(square, attack_square, piece)
-                    .with_capture(Piece::new(piece.get_color().opposite(), PieceKind::Pawn))
-                    .with_en_passant_square(attack_square.offset(0, -dir).unwrap()),
-            )
-        }
+    let enemies = board.get_color_bitboard(piece.get_color().opposite());
+    let attacks = pawn_attacks(square, piece.get_color());
+    for attack_square in squares(attacks & enemies) {
+        add_pawn_move(
+            Move::new(square, attack_square, piece)
+                .with_capture_option(board.get_piece(attack_square)),
+            moves,
+        );
     }
 
-    return moves
-        .iter()
-        .flat_map(|m| {
-            if m.to.rank == 0 || m.to.rank == 7 {
-                [
-                    PieceKind::Queen,
-                    PieceKind::Rook,
-                    PieceKind::Bishop,
-                    PieceKind::Knight,
-                ]
-                .iter()
-                .map(|&kind| {
-                    Move::new(m.from, m.to, piece)
-                        .with_capture_option(m.capture)
-                        .with_promotion(Piece::new(piece.get_color(), kind))
-                })
-                .collect::<Vec<_>>()
-            } else {
-                vec![*m]
-            }
-        })
-        .collect();
+    // en passant
+    // if both capture and en_passant_square are set, the en_passant square points to captured pawn
+    if let Some(en_passant_square) = board.get_game_state().en_passant_square
+        && attacks & square_bitboard(en_passant_square) != EMPTY
+    {
+        moves.push(
+            Move::new(square, en_passant_square, piece)
+                .with_capture(Piece::new(piece.get_color().opposite(), PieceKind::Pawn))
+                .with_en_passant_square(en_passant_square.offset(0, -dir).unwrap()),
+        )
+    }
 }
 
-fn get_castling_moves(board: &Board, color: Color) -> Vec<Move> {
-    let mut moves = Vec::new();
+fn add_castling_moves(board: &Board, color: Color, moves: &mut Vec<Move>) {
     if board.is_color_in_check(color) {
-        return moves;
+        return;
     }
     let castling_rights = board.get_game_state().castling_rights;
     let (piece, rank) = match color {
@@ -303,96 +251,106 @@ fn get_castling_moves(board: &Board, color: Color) -> Vec<Move> {
                 .with_castling(Square { file: 0, rank }, Square { file: 3, rank }),
         );
     }
+}
 
-    moves
+/// Bitboard of pieces of the opposite color of `attacked` that attack the square.
+pub fn get_square_attackers_bitboard(board: &Board, square: Square, attacked: Color) -> Bitboard {
+    get_square_attackers_with_occupancy(board, square, attacked, board.get_occupancy())
 }
 
-pub fn get_square_attackers(
+/// Same as [`get_square_attackers_bitboard`] but sliders are blocked by `occupancy` instead of the
+/// pieces on the board, e.g. to see through a king that is about to move.
+pub(crate) fn get_square_attackers_with_occupancy(
     board: &Board,
     square: Square,
     attacked: Color,
-) -> Vec<(Piece, Square)> {
-    let mut attackers = Vec::new();
-
-    let piece = Piece::new(attacked, PieceKind::Pawn); // dummy piece to generate moves
+    occupancy: Bitboard,
+) -> Bitboard {
+    let attacker = attacked.opposite();
+    let queens = board.get_piece_bitboard(Piece::new(attacker, PieceKind::Queen));
+    let diagonal = board.get_piece_bitboard(Piece::new(attacker, PieceKind::Bishop)) | queens;
+    let orthogonal = board.get_piece_bitboard(Piece::new(attacker, PieceKind::Rook)) | queens;
+    let knights = board.get_piece_bitboard(Piece::new(attacker, PieceKind::Knight));
+    let kings = board.get_piece_bitboard(Piece::new(attacker, PieceKind::King));
+    let pawns = board.get_piece_bitboard(Piece::new(attacker, PieceKind::Pawn));
+
+    (bishop_attacks(square, occupancy) & diagonal)
+        | (rook_attacks(square, occupancy) & orthogonal)
+        | (knight_attacks(square) & knights)
+        | (king_attacks(square) & kings)
+        // a pawn of the attacked color standing on the square attacks exactly the squares
+        // from which enemy pawns could capture it
+        | (pawn_attacks(square, attacked) & pawns)
+}
 
-    // Diagonal attackers
-    attackers.extend(
-        get_moves_in_line(board, square, piece, BISHOP_OFFSETS.to_vec())
-            .into_iter()
-            .filter(|m| m.capture.is_some())
-            .filter(|m| {
-                [PieceKind::Bishop, PieceKind::Queen].contains(&m.capture.unwrap().get_kind())
-            })
-            .map(|m| (m.capture.unwrap(), m.to)),
-    );
-
-    // Orthogonal attackers
-    attackers.extend(
-        get_moves_in_line(board, square, piece, ROOK_OFFSETS.to_vec())
-            .into_iter()
-            .filter(|m| m.capture.is_some())
-            .filter(|m| {
-                [PieceKind::Rook, PieceKind::Queen].contains(&m.capture.unwrap().get_kind())
-            })
-            .map(|m| (m.capture.unwrap(), m.to)),
-    );
-
-    // Knight attackers
-    attackers.extend(
-        get_moves_at_offsets(board, square, piece, KNIGHT_OFFSETS.to_vec())
-            .into_iter()
-            .filter(|m| m.capture.is_some())
-            .filter(|m| m.capture.unwrap().get_kind() == PieceKind::Knight)
-            .map(|m| (m.capture.unwrap(), m.to)),
-    );
-
-    // King attackers
-    attackers.extend(
-        get_moves_at_offsets(board, square, piece, KING_OFFSETS.to_vec())
-            .into_iter()
-            .filter(|m| m.capture.is_some())
-            .filter(|m| m.capture.unwrap().get_kind() == PieceKind::King)
-            .map(|m| (m.capture.unwrap(), m.to)),
-    );
-
-    // Pawn attackers
-    let pawn_attack_offsets = match attacked {
-        Color::White => WHITE_PAWN_ATTACK_OFFSETS,
-        Color::Black => BLACK_PAWN_ATTACK_OFFSETS,
+/// Pieces of `color` that are the only piece standing between their king and an enemy slider.
+pub(crate) fn get_pinned_bitboard(board: &Board, color: Color) -> Bitboard {
+    let Some(king_square) = board.get_king_square(color) else {
+        return EMPTY;
     };
-    attackers.extend(
-        get_moves_at_offsets(board, square, piece, pawn_attack_offsets.to_vec())
-            .into_iter()
-            .filter(|m| m.capture.is_some())
-            .filter(|m| m.capture.unwrap().get_kind() == PieceKind::Pawn)
-            .map(|m| (m.capture.unwrap(), m.to)),
-    );
+    let enemy = color.opposite();
+    let own = board.get_color_bitboard(color);
+    let occupancy = board.get_occupancy();
+    let queens = board.get_piece_bitboard(Piece::new(enemy, PieceKind::Queen));
+    let diagonal = board.get_piece_bitboard(Piece::new(enemy, PieceKind::Bishop)) | queens;
+    let orthogonal = board.get_piece_bitboard(Piece::new(enemy, PieceKind::Rook)) | queens;
+
+    let mut pinned = EMPTY;
+    for (is_diagonal, sliders) in [(true, diagonal), (false, orthogonal)] {
+        if sliders == EMPTY {
+            continue;
+        }
+        let attacks = |occupancy| {
+            if is_diagonal {
+                bishop_attacks(king_square, occupancy)
+            } else {
+                rook_attacks(king_square, occupancy)
+            }
+        };
+        for blocker in squares(attacks(occupancy) & own) {
+            let blocker_bitboard = square_bitboard(blocker);
+            if attacks(occupancy & !blocker_bitboard) & sliders != EMPTY {
+                pinned |= blocker_bitboard;
+            }
+        }
+    }
+    pinned
+}
 
-    attackers
+pub fn get_square_attackers(
+    board: &Board,
+    square: Square,
+    attacked: Color,
+) -> Vec<(Piece, Square)> {
+    squares(get_square_attackers_bitboard(board, square, attacked))
+        .map(|attacker_square| (board.get_piece(attacker_square).unwrap(), attacker_square))
+        .collect()
 }
 
 pub fn get_moves_from_square(board: &Board, square: Square) -> Vec<Move> {
-    let piece_option = board.get_piece(square);
-    if piece_option.is_none() {
-        return Vec::new();
-    }
+    let mut moves = Vec::new();
+    add_moves_from_square(board, square, &mut moves);
+    moves
+}
 
-    let piece = piece_option.unwrap();
-    let kind = piece.get_kind();
+pub(crate) fn add_moves_from_square(board: &Board, square: Square, moves: &mut Vec<Move>) {
+    let Some(piece) = board.get_piece(square) else {
+        return;
+    };
 
-    match kind {
-        PieceKind::Pawn => get_moves_for_pawn(board, square, piece),
-        PieceKind::Knight => get_moves_at_offsets(board, square, piece, KNIGHT_OFFSETS.to_vec()),
-        PieceKind::Bishop => get_moves_in_line(board, square, piece, BISHOP_OFFSETS.to_vec()),
-        PieceKind::Rook => get_moves_in_line(board, square, piece, ROOK_OFFSETS.to_vec()),
-        PieceKind::Queen => get_moves_in_line(board, square, piece, QUEEN_OFFSETS.to_vec()),
+    let occupancy = board.get_occupancy();
+    let targets = match piece.get_kind() {
+        PieceKind::Pawn => return add_moves_for_pawn(board, square, piece, moves),
+        PieceKind::Knight => knight_attacks(square),
+        PieceKind::Bishop => bishop_attacks(square, occupancy),
+        PieceKind::Rook => rook_attacks(square, occupancy),
+        PieceKind::Queen => queen_attacks(square, occupancy),
         PieceKind::King => {
-            let mut moves = get_moves_at_offsets(board, square, piece, KING_OFFSETS.to_vec());
-            moves.extend(get_castling_moves(board, piece.get_color()));
-            moves
+            add_castling_moves(board, piece.get_color(), moves);
+            king_attacks(square)
         }
-    }
+    };
+    add_moves_to_targets(board, square, piece, targets, moves);
 }
 
 impl Display for Move {
@@ -407,7 +365,6 @@ impl Display for Move {
         )
     }
 }
-// TODO fen fix broke all test fens. Fix them, future me.
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -419,10 +376,16 @@ mod tests {
     mod piece_moves {
         use super::*;
 
+        fn get_castling_moves(board: &Board, color: Color) -> Vec<Move> {
+            let mut moves = Vec::new();
+            add_castling_moves(board, color, &mut moves);
+            moves
+        }
+
         #[test]
         fn test_moves_for_paws() {
             let board =
-                Board::from_fen("rnbqkbnr/p1pppppp/8/1p6/2P5/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+                Board::from_fen("rnbqkbnr/pppppppp/8/2p5/1P6/8/P1PPPPPP/RNBQKBNR w KQkq - 0 1");
 
             let moves = get_moves_from_square(&board, Square { file: 1, rank: 3 });
 
@@ -446,7 +409,7 @@ mod tests {
         #[test]
         fn test_moves_for_knight() {
             let board =
-                Board::from_fen("rnbqkbnr/pppppppp/P7/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+                Board::from_fen("rnbqkbnr/pppppppp/8/8/8/p7/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
             let moves = get_moves_from_square(&board, Square { file: 1, rank: 0 });
             assert_eq!(moves.len(), 2);
         }
@@ -454,21 +417,21 @@ mod tests {
         #[test]
         fn test_moves_for_bishop() {
             let board =
-                Board::from_fen("rnbqk1nr/pppppppp/8/3b4/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+                Board::from_fen("rnbqkbnr/pppppppp/8/8/3B4/8/PPPPPPPP/RNBQK1NR w KQkq - 0 1");
             let moves = get_moves_from_square(&board, Square { file: 3, rank: 3 });
             assert_eq!(moves.len(), 8);
         }
 
         #[test]
         fn test_moves_for_rook() {
-            let board = Board::from_fen("r7/8/8/8/8/8/PPPPPPPP/8 w KQkq - 0 1");
+            let board = Board::from_fen("8/pppppppp/8/8/8/8/8/R7 w KQkq - 0 1");
             let moves = get_moves_from_square(&board, Square { file: 0, rank: 0 });
             assert_eq!(moves.len(), 13);
         }
 
         #[test]
         fn test_moves_for_queen() {
-            let board = Board::from_fen("8/8/8/8/3q4/8/8/8 w KQkq - 0 1");
+            let board = Board::from_fen("8/8/8/3Q4/8/8/8/8 w KQkq - 0 1");
             let mut moves = get_moves_from_square(&board, Square { file: 3, rank: 4 });
             moves.sort_by_key(|m| m.to);
             assert_eq!(moves.len(), 27);
@@ -476,14 +439,14 @@ mod tests {
 
         #[test]
         fn test_moves_for_king() {
-            let board = Board::from_fen("rnbqkbnr/8/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+            let board = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/8/RNBQKBNR w KQkq - 0 1");
             let moves = get_moves_from_square(&board, Square { file: 4, rank: 0 });
             assert_eq!(moves.len(), 3);
         }
 
         #[test]
         fn test_castling_moves_white() {
-            let board = Board::from_fen("r3k2r/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+            let board = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1");
             let moves = get_castling_moves(&board, Color::White);
             assert_eq!(moves.len(), 2);
             assert!(moves.iter().any(|m| m.castling_rook_from_to
@@ -505,7 +468,7 @@ mod tests {
 
         #[test]
         fn test_no_castling_when_square_attacked() {
-            let board = Board::from_fen("6r1/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+            let board = Board::from_fen("r3k2r/8/8/8/8/8/8/6R1 w KQkq - 0 1");
             let moves = get_castling_moves(&board, Color::Black);
             assert_eq!(moves.len(), 1);
             assert!(moves.iter().any(|m| m.castling_rook_from_to
@@ -524,13 +487,13 @@ mod tests {
 
         #[test]
         fn test_no_castling_when_in_check() {
-            let board = Board::from_fen("r3k2r/8/8/4R3/8/8/8/8 w KQkq - 0 1");
+            let board = Board::from_fen("8/8/8/8/4r3/8/8/R3K2R w KQkq - 0 1");
             let moves = get_castling_moves(&board, Color::White);
             assert_eq!(moves.len(), 0);
         }
     }
 
-    // TODO fix these too. Also fix algebraic notation in general.
+    // TODO fix short algebraic notation too
     mod algebraic_notation {
         use super::*;
 
@@ -559,6 +522,76 @@ mod tests {
             .with_promotion(Piece::WHITE_QUEEN);
             assert_eq!(m_promotion.to_long_algebraic_notation(), "g7g8Q");
         }
+
+        fn uci_round_trip(fen: &str, notation: &str) -> Move {
+            let board = Board::from_fen(fen);
+            let m = Move::from_uci_notation(&board, notation).unwrap();
+            assert_eq!(m.to_uci_notation(), notation);
+            m
+        }
+
+        #[test]
+        fn test_uci_notation() {
+            let m = uci_round_trip(
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "e2e4",
+            );
+            assert_eq!(m.piece, Piece::WHITE_PAWN);
+
+            // promotions, capturing or not and to any piece
+            let fen = "r3k3/1P6/8/8/8/8/1p6/4K2R w K - 0 1";
+            let m = uci_round_trip(fen, "b7b8q");
+            assert_eq!(m.promotion, Some(Piece::WHITE_QUEEN));
+            let m = uci_round_trip(fen, "b7a8n");
+            assert_eq!(m.promotion, Some(Piece::WHITE_KNIGHT));
+            assert_eq!(m.capture, Some(Piece::BLACK_ROOK));
+            let board = Board::from_fen(fen);
+            assert!(Move::from_uci_notation(&board, "b7b8").is_none());
+            assert_eq!(
+                Move::from_uci_notation(&board, "b7b8Q")
+                    .unwrap()
+                    .to_uci_notation(),
+                "b7b8q"
+            );
+            let m = uci_round_trip("r3k3/1P6/8/8/8/8/1p6/4K2R b K - 0 1", "b2b1r");
+            assert_eq!(m.promotion, Some(Piece::BLACK_ROOK));
+
+            // castling is the king move
+            let m = uci_round_trip(fen, "e1g1");
+            assert!(m.castling_rook_from_to.is_some());
+            let m = uci_round_trip("r3k3/8/8/8/8/8/8/4K3 b q - 0 1", "e8c8");
+            assert!(m.castling_rook_from_to.is_some());
+
+            // en passant
+            let m = uci_round_trip("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6");
+            assert_eq!(m.capture, Some(Piece::BLACK_PAWN));
+            assert!(m.en_passant_square.is_some());
+        }
+
+        #[test]
+        fn test_invalid_uci_notation() {
+            let board = Board::default();
+            for notation in ["", "e2", "e2e5", "e2e4q", "i2i4", "e1g1", "Pe2e4"] {
+                assert!(
+                    Move::from_uci_notation(&board, notation).is_none(),
+                    "{}",
+                    notation
+                );
+            }
+        }
+
+        #[test]
+        fn test_uci_notation_round_trip() {
+            let board = Board::from_fen(
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            );
+            for m in board.get_legal_moves() {
+                assert_eq!(
+                    Move::from_uci_notation(&board, &m.to_uci_notation()),
+                    Some(m)
+                );
+            }
+        }
     }
 
     mod square_attacked {
@@ -566,7 +599,7 @@ mod tests {
 
         #[test]
         fn test_attacked_by_pawn() {
-            let board = Board::from_fen("8/8/8/8/8/3P4/8/8 w - - 0 1");
+            let board = Board::from_fen("8/8/3p4/8/8/8/8/8 w - - 0 1");
             let attackers = get_square_attackers(&board, Square { file: 2, rank: 4 }, Color::White);
             assert_eq!(attackers.len(), 1);
             assert!(attackers.iter().any(|a| a.0.get_kind() == PieceKind::Pawn));
@@ -574,7 +607,7 @@ mod tests {
 
         #[test]
         fn test_attacked_by_knight() {
-            let board = Board::from_fen("8/8/3N4/8/8/8/8/8 w - - 0 1");
+            let board = Board::from_fen("8/8/8/8/8/3n4/8/8 w - - 0 1");
             let attackers = get_square_attackers(&board, Square { file: 2, rank: 4 }, Color::White);
             assert_eq!(attackers.len(), 1);
             assert!(
@@ -586,7 +619,7 @@ mod tests {
 
         #[test]
         fn test_attacked_by_bishop() {
-            let board = Board::from_fen("8/8/8/3B4/8/8/8/8 w - - 0 1");
+            let board = Board::from_fen("8/8/8/8/3b4/8/8/8 w - - 0 1");
             let attackers = get_square_attackers(&board, Square { file: 0, rank: 0 }, Color::White);
             assert_eq!(attackers.len(), 1);
             assert!(
@@ -598,7 +631,7 @@ mod tests {
 
         #[test]
         fn test_attacked_by_rook() {
-            let board = Board::from_fen("R7/8/8/8/8/8/8/8 w - - 0 1");
+            let board = Board::from_fen("8/8/8/8/8/8/8/r7 w - - 0 1");
             let attackers = get_square_attackers(&board, Square { file: 0, rank: 7 }, Color::White);
             assert_eq!(attackers.len(), 1);
             assert!(attackers.iter().any(|a| a.0.get_kind() == PieceKind::Rook));
@@ -606,7 +639,7 @@ mod tests {
 
         #[test]
         fn test_attacked_by_queen() {
-            let board = Board::from_fen("8/8/8/3Q4/8/8/8/8 w - - 0 1");
+            let board = Board::from_fen("8/8/8/8/3q4/8/8/8 w - - 0 1");
             let attackers = get_square_attackers(&board, Square { file: 4, rank: 3 }, Color::White);
             assert_eq!(attackers.len(), 1);
             assert!(attackers.iter().any(|a| a.0.get_kind() == PieceKind::Queen));
@@ -614,7 +647,7 @@ mod tests {
 
         #[test]
         fn test_attacked_by_king() {
-            let board = Board::from_fen("8/8/8/8/3K4/8/8/8 w - - 0 1");
+            let board = Board::from_fen("8/8/8/3k4/8/8/8/8 w - - 0 1");
             let attackers = get_square_attackers(&board, Square { file: 4, rank: 3 }, Color::White);
             assert_eq!(attackers.len(), 1);
             assert!(attackers.iter().any(|a| a.0.get_kind() == PieceKind::King));
@@ -622,9 +655,82 @@ mod tests {
 
         #[test]
         fn test_attacked_by_multiple_pieces() {
-            let board = Board::from_fen("8/8/3N4/4R3/8/3P4/8/8 w - - 0 1");
+            let board = Board::from_fen("8/8/3p4/8/4r3/3n4/8/8 w - - 0 1");
             let attackers = get_square_attackers(&board, Square { file: 4, rank: 4 }, Color::White);
             assert!(attackers.len() == 3);
         }
     }
+    /// Node counts from https://www.chessprogramming.org/Perft_Results
+    mod perft {
+        use super::*;
+
+        fn perft(board: &mut Board, depth: u8) -> u64 {
+            assert_eq!(board.hash(), board.compute_hash(), "{}", board.to_fen());
+            let moves = board.get_legal_moves();
+            if depth == 1 {
+                return moves.len() as u64;
+            }
+            let mut nodes = 0;
+            for m in moves {
+                board.apply_move(&m);
+                nodes += perft(board, depth - 1);
+                board.undo_move(&m);
+            }
+            nodes
+        }
+
+        fn assert_perft(fen: &str, expected: &[u64]) {
+            let mut board = Board::from_fen(fen);
+            for (depth, nodes) in expected.iter().enumerate() {
+                assert_eq!(
+                    perft(&mut board, depth as u8 + 1),
+                    *nodes,
+                    "{} at depth {}",
+                    fen,
+                    depth + 1
+                );
+            }
+            assert_eq!(board.to_fen(), fen);
+        }
+
+        #[test]
+        fn test_perft_initial_position() {
+            assert_perft(
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                &[20, 400, 8902],
+            );
+        }
+
+        #[test]
+        fn test_perft_kiwipete() {
+            assert_perft(
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                &[48, 2039, 97862],
+            );
+        }
+
+        #[test]
+        fn test_perft_position_3() {
+            assert_perft(
+                "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+                &[14, 191, 2812, 43238],
+            );
+        }
+
+        #[test]
+        fn test_perft_position_4() {
+            assert_perft(
+                "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+                &[6, 264, 9467],
+            );
+        }
+
+        #[test]
+        fn test_perft_position_5() {
+            assert_perft(
+                "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+                &[44, 1486, 62379],
+            );
+        }
+    }
 }
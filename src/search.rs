@@ -1,30 +1,105 @@
+mod alphabeta;
+mod config;
 mod minimax;
+mod ordering;
+mod pv;
+mod quiescence;
 mod random;
+mod score;
+mod time;
+mod transposition;
 
 use crate::{board::Board, evaluate::Evaluator, r#move::Move};
+pub use alphabeta::AlphaBetaSearch;
+pub use config::SearchConfig;
 pub use minimax::MinimaxSearch;
+pub use quiescence::QuiescenceSearch;
 pub use random::RandomSearch;
+pub use score::{MATE_SCORE, MAX_PLY, Score, clamp_eval, is_mate_score, mated_in};
+pub use time::{TimeControl, TimeManager};
+pub use transposition::{Bound, DEFAULT_HASH_SIZE_MB};
 
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 use std::sync::{
-    Arc,
+    Arc, OnceLock,
     atomic::{AtomicBool, Ordering},
 };
 use std::time::{Duration, Instant};
 
+/// Number of nodes between checks of the time and node limits inside the search tree.
+const LIMITS_CHECK_INTERVAL: usize = 1024;
+/// Time between progress reports of a running iteration, for searches that send them.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
 #[derive(Debug, Clone, Default)]
 pub struct SearchLimits {
     pub max_depth: Option<u8>,
+    /// Hard deadline, checked inside the search tree.
     pub max_time: Option<Duration>,
     pub max_nodes: Option<usize>,
+    /// No new iteration of iterative deepening is started after this, see [`TimeManager`].
+    pub soft_time: Option<Duration>,
+    /// Set when pondering: the time limits only run from the instant stored on ponderhit.
+    pub ponderhit: Option<Arc<OnceLock<Instant>>>,
+}
+
+impl SearchLimits {
+    /// Time counted against the time limits of a search started at `start_time`, `None` while
+    /// pondering.
+    pub fn get_clock_elapsed(&self, start_time: &Instant) -> Option<Duration> {
+        match &self.ponderhit {
+            None => Some(start_time.elapsed()),
+            Some(ponderhit) => ponderhit.get().map(Instant::elapsed),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct SearchInfo {
     pub depth: u8,
-    pub score: i32,
-    pub nodes: usize,
+    pub seldepth: u8, // Deepest ply reached by the main thread, quiescence included
+    pub multipv: usize, // Rank of the line, starting at 1
+    pub score: Score,
+    pub bound: Bound, // Exact, or the side of the search window the score fell out of
+    pub nodes: usize, // All nodes of all threads, quiescence included
+    pub qnodes: usize, // Nodes in quiescence search of the main thread
     pub time: Duration,
-    pub pv: Vec<Move>, // Principal variation (best line)
+    pub pv: Vec<Move>,             // Principal variation (best line)
+    pub hashfull: u16,             // Permille of the transposition table in use
+    pub tt_probes: usize,          // Transposition table lookups of the main thread
+    pub tt_hits: usize,            // Of those, the ones finding an entry for the position
+    pub beta_cutoffs: usize,       // Nodes of the main thread where a move failed high
+    pub first_move_cutoffs: usize, // Of those, the ones where the first move searched did
+    pub currmove: Option<Move>,    // Root move being searched, in progress reports
+    pub currmovenumber: usize,     // Its number in the root move order, starting at 1
+    // Periodic report while an iteration runs, the score and line being those of the last
+    // completed iteration
+    pub progress: bool,
+}
+
+impl SearchInfo {
+    /// Nodes searched per second by all threads.
+    pub fn get_nps(&self) -> usize {
+        (self.nodes as u128 * 1000 / self.time.as_millis().max(1)) as usize
+    }
+
+    /// Share of transposition table lookups that found an entry, between 0 and 1.
+    pub fn get_tt_hit_rate(&self) -> f64 {
+        if self.tt_probes == 0 {
+            0.0
+        } else {
+            self.tt_hits as f64 / self.tt_probes as f64
+        }
+    }
+}
+
+/// Root move with its score and principal variation, as ranked by a MultiPV search.
+#[derive(Debug, Clone)]
+pub struct RankedMove {
+    pub mv: Move,
+    pub score: Score,
+    pub pv: Vec<Move>,
 }
 
 pub fn should_stop(
@@ -37,22 +112,26 @@ pub fn should_stop(
         return true;
     }
 
-    if let Some(max_time) = limits.max_time {
-        if start_time.elapsed() >= max_time {
-            return true;
-        }
+    if let Some(max_time) = limits.max_time
+        && limits
+            .get_clock_elapsed(start_time)
+            .is_some_and(|elapsed| elapsed >= max_time)
+    {
+        return true;
     }
 
-    if let Some(max_nodes) = limits.max_nodes {
-        if nodes_searched >= max_nodes {
-            return true;
-        }
+    if let Some(max_nodes) = limits.max_nodes
+        && nodes_searched >= max_nodes
+    {
+        return true;
     }
 
     false
 }
 
 pub trait SearchAlgorithm {
+    /// Best move of `board` within `limits`. Each completed iteration is reported to
+    /// `info_callback`, along with progress reports in between for searches that send them.
     fn search(
         &mut self,
         board: &Board,
@@ -62,6 +141,29 @@ pub trait SearchAlgorithm {
         info_callback: Option<Box<dyn Fn(SearchInfo) + Send>>,
     ) -> Option<Move>;
 
+    /// Resizes the transposition table of searches that have one, dropping its content.
+    fn set_hash_size(&mut self, _size_mb: usize) {}
+
+    /// Forgets what was learned in previous searches, e.g. when a new game starts.
+    fn clear(&mut self) {}
+
+    /// Number of best root moves searched with their own principal variation, reported with
+    /// increasing [`SearchInfo::multipv`] after each iteration. One by default.
+    fn set_multi_pv(&mut self, _count: usize) {}
+
+    /// Number of threads searching in parallel, for searches that support it. With one, the
+    /// search is deterministic.
+    fn set_threads(&mut self, _count: usize) {}
+
+    /// Selective search techniques to use, for searches that have them.
+    fn set_config(&mut self, _config: SearchConfig) {}
+
+    /// Best root moves of the last completed iteration of the last search, best first. Empty for
+    /// searches without an evaluation.
+    fn get_ranked_moves(&self) -> Vec<RankedMove> {
+        Vec::new()
+    }
+
     fn search_simple(
         &mut self,
         board: &Board,
@@ -77,3 +179,59 @@ pub trait SearchAlgorithm {
         )
     }
 }
+
+/// Search algorithms selectable by name from the binaries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchKind {
+    #[default]
+    AlphaBeta,
+    Minimax,
+    Random,
+}
+
+impl SearchKind {
+    pub const ALL: [SearchKind; 3] = [
+        SearchKind::AlphaBeta,
+        SearchKind::Minimax,
+        SearchKind::Random,
+    ];
+
+    pub fn create(&self) -> Box<dyn SearchAlgorithm + Send> {
+        match self {
+            SearchKind::AlphaBeta => Box::new(AlphaBetaSearch::new()),
+            SearchKind::Minimax => Box::new(MinimaxSearch::new()),
+            SearchKind::Random => Box::new(RandomSearch {}),
+        }
+    }
+
+    /// The next kind in [`SearchKind::ALL`], wrapping around.
+    pub fn next(&self) -> SearchKind {
+        let idx = SearchKind::ALL
+            .iter()
+            .position(|kind| kind == self)
+            .unwrap();
+        SearchKind::ALL[(idx + 1) % SearchKind::ALL.len()]
+    }
+}
+
+impl Display for SearchKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            SearchKind::AlphaBeta => "AlphaBeta",
+            SearchKind::Minimax => "Minimax",
+            SearchKind::Random => "Random",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+impl FromStr for SearchKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SearchKind::ALL
+            .into_iter()
+            .find(|kind| kind.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown search algorithm: {}", s))
+    }
+}
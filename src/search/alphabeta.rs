@@ -0,0 +1,1209 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+use std::thread;
+use std::time::Instant;
+
+use super::{
+    LIMITS_CHECK_INTERVAL, PROGRESS_INTERVAL, RankedMove, Score, SearchAlgorithm, SearchConfig,
+    SearchInfo, SearchLimits, TimeManager, clamp_eval, is_mate_score, mated_in,
+    ordering::{MovePicker, OrderingHeuristics},
+    pv::{PvTable, is_legal_line},
+    quiescence::QuiescenceSearch,
+    transposition::{Bound, TranspositionTable},
+};
+use crate::{Board, board::piece::PieceKind::Pawn, evaluate::Evaluator, r#move::Move};
+
+const INFINITY: i32 = 1000000;
+
+/// Checks aren't extended past this ply, so that long series of checks can't blow up the search.
+const MAX_EXTENSION_PLY: u8 = 64;
+/// Minimum depth for null-move pruning, and depth reduction of the null move search, which grows
+/// by one every [`NULL_MOVE_DEPTH_STEP`] plies of depth.
+const NULL_MOVE_MIN_DEPTH: u8 = 3;
+const NULL_MOVE_REDUCTION: u8 = 2;
+const NULL_MOVE_DEPTH_STEP: u8 = 6;
+/// Deepest nodes where reverse futility pruning applies, and its margin per ply of depth, in
+/// hundredths of a pawn.
+const REVERSE_FUTILITY_MAX_DEPTH: u8 = 3;
+const REVERSE_FUTILITY_MARGIN: i32 = 120;
+/// Deepest nodes where futility pruning applies, and its margin per ply of depth, in hundredths
+/// of a pawn.
+const FUTILITY_MAX_DEPTH: u8 = 2;
+const FUTILITY_MARGIN: i32 = 150;
+/// Deepest nodes where captures are pruned by static exchange evaluation, and the material they
+/// may lose per ply of depth, in centipawns.
+const SEE_PRUNING_MAX_DEPTH: u8 = 3;
+const SEE_PRUNING_MARGIN: i32 = 100;
+/// Minimum depth for late move reductions, and number of moves searched at full depth before
+/// them. Moves ordered later than [`LMR_DEEP_MOVES`] are reduced one more ply at
+/// [`LMR_DEEP_MIN_DEPTH`] and beyond.
+const LMR_MIN_DEPTH: u8 = 3;
+const LMR_FULL_DEPTH_MOVES: usize = 3;
+const LMR_DEEP_MOVES: usize = 8;
+const LMR_DEEP_MIN_DEPTH: u8 = 6;
+/// Minimum depth for aspiration windows, and their initial half-width in hundredths of a pawn,
+/// doubled every time the score falls out of the window.
+const ASPIRATION_MIN_DEPTH: u8 = 4;
+const ASPIRATION_WINDOW: i32 = 50;
+
+/// Fail-soft alpha-beta with principal variation search: the first move of a node is searched
+/// with the full window, the rest with a null window and only re-searched when they beat alpha.
+/// The transposition table is kept between searches. The search is made selective by the
+/// techniques of its [`SearchConfig`].
+///
+/// Iterations start with an aspiration window around the score of the previous one, widened
+/// when the score falls out of it. An interrupted iteration still provides the best move once a
+/// root move has beaten the window, as the best move of the previous iteration is searched first.
+///
+/// With more than one thread, helper threads run the same search over the shared transposition
+/// table (Lazy SMP), filling it with results the main thread picks up. Only the main thread
+/// reports and picks the best move.
+pub struct AlphaBetaSearch {
+    tt: Arc<TranspositionTable>,
+    quiescence: QuiescenceSearch,
+    pv_table: PvTable,
+    nodes_searched: usize,
+    last_limits_check: usize,
+    limits: SearchLimits,
+    start_time: Instant,
+    stop_flag: Arc<AtomicBool>,
+    is_stopped: bool,
+    multi_pv: usize,
+    config: SearchConfig,
+    heuristics: OrderingHeuristics,
+    // Moves from the root to the current node, `None` for null moves
+    played_moves: Vec<Option<Move>>,
+    // Beta cutoffs in the current search, and how many of them came from the first move searched
+    beta_cutoffs: usize,
+    first_move_cutoffs: usize,
+    // Transposition table lookups in the current search, and how many found an entry
+    tt_probes: usize,
+    tt_hits: usize,
+    // Depth of the running iteration, and deepest ply it reached outside quiescence search
+    iteration_depth: u8,
+    seldepth: u8,
+    // Root move being searched, with its number in the root move order
+    root_move: Option<(Move, usize)>,
+    // Receives the reports of the current search, `None` for helper threads
+    info_callback: Option<Box<dyn Fn(SearchInfo) + Send>>,
+    last_progress: Instant,
+    // Best root moves of the last completed iteration
+    ranked_moves: Vec<RankedMove>,
+    // Root moves already ranked in the current iteration, skipped when searching the next line
+    excluded_root_moves: Vec<Move>,
+    // Score and line of the best root move of the line being searched, once one beat the window
+    partial_line: Option<(i32, Vec<Move>)>,
+    threads: usize,
+    // Searches of the helper threads, kept between searches and only rebuilt when the number of
+    // threads changes
+    helpers: Vec<AlphaBetaSearch>,
+    // Nodes searched by the helper threads of the current search
+    helper_nodes: Arc<AtomicUsize>,
+    // For a helper thread, the main search's count its nodes are added to
+    main_helper_nodes: Option<Arc<AtomicUsize>>,
+}
+
+impl AlphaBetaSearch {
+    pub fn new() -> Self {
+        Self::with_table(Arc::new(TranspositionTable::default()))
+    }
+
+    /// Search using `tt` as its transposition table, which may be shared with other searches.
+    fn with_table(tt: Arc<TranspositionTable>) -> Self {
+        Self {
+            tt,
+            quiescence: QuiescenceSearch::new(),
+            pv_table: PvTable::new(),
+            nodes_searched: 0,
+            last_limits_check: 0,
+            limits: SearchLimits::default(),
+            start_time: Instant::now(),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            is_stopped: false,
+            multi_pv: 1,
+            config: SearchConfig::default(),
+            heuristics: OrderingHeuristics::new(),
+            played_moves: Vec::new(),
+            beta_cutoffs: 0,
+            first_move_cutoffs: 0,
+            tt_probes: 0,
+            tt_hits: 0,
+            iteration_depth: 0,
+            seldepth: 0,
+            root_move: None,
+            info_callback: None,
+            last_progress: Instant::now(),
+            ranked_moves: Vec::new(),
+            excluded_root_moves: Vec::new(),
+            partial_line: None,
+            threads: 1,
+            helpers: Vec::new(),
+            helper_nodes: Arc::new(AtomicUsize::new(0)),
+            main_helper_nodes: None,
+        }
+    }
+
+    /// Helper thread of a parallel search, sharing its transposition table.
+    fn new_helper(main: &AlphaBetaSearch) -> Self {
+        let mut helper = Self::with_table(Arc::clone(&main.tt));
+        helper.main_helper_nodes = Some(Arc::clone(&main.helper_nodes));
+        helper
+    }
+
+    /// Prepares a helper for the search the main thread is starting, until `stop_flag` tells it
+    /// the main thread is done.
+    fn reset_helper(&mut self, main: &AlphaBetaSearch, stop_flag: Arc<AtomicBool>) {
+        let limits = SearchLimits {
+            max_depth: main.limits.max_depth,
+            ..Default::default()
+        };
+        self.reset(limits, stop_flag);
+        self.config = main.config;
+    }
+
+    fn reset(&mut self, limits: SearchLimits, stop_flag: Arc<AtomicBool>) {
+        self.start_time = Instant::now();
+        self.nodes_searched = 0;
+        self.quiescence.nodes_searched = 0;
+        self.last_limits_check = 0;
+        self.limits = limits;
+        self.stop_flag = stop_flag;
+        self.is_stopped = false;
+        self.heuristics.new_search();
+        self.played_moves.clear();
+        self.beta_cutoffs = 0;
+        self.first_move_cutoffs = 0;
+        self.tt_probes = 0;
+        self.tt_hits = 0;
+        self.root_move = None;
+        self.last_progress = Instant::now();
+        self.ranked_moves.clear();
+        self.helper_nodes.store(0, Ordering::Relaxed);
+    }
+
+    /// Nodes searched so far, quiescence included.
+    fn get_nodes(&self) -> usize {
+        self.nodes_searched + self.quiescence.nodes_searched
+    }
+
+    /// Nodes searched so far by all threads.
+    fn get_total_nodes(&self) -> usize {
+        self.get_nodes() + self.helper_nodes.load(Ordering::Relaxed)
+    }
+
+    fn should_stop(&mut self) -> bool {
+        let nodes = self.get_nodes();
+        if !self.is_stopped && nodes - self.last_limits_check >= LIMITS_CHECK_INTERVAL {
+            if let Some(main_helper_nodes) = &self.main_helper_nodes {
+                main_helper_nodes.fetch_add(nodes - self.last_limits_check, Ordering::Relaxed);
+            }
+            self.last_limits_check = nodes;
+            self.is_stopped = super::should_stop(
+                &self.limits,
+                &self.start_time,
+                self.get_total_nodes(),
+                &self.stop_flag,
+            );
+            if !self.is_stopped && self.last_progress.elapsed() >= PROGRESS_INTERVAL {
+                self.report_progress();
+            }
+        }
+        self.is_stopped || self.stop_flag.load(Ordering::Relaxed)
+    }
+
+    fn alphabeta(
+        &mut self,
+        board: &mut Board,
+        evaluator: &Arc<dyn Evaluator>,
+        depth: u8,
+        ply: u8,
+        mut alpha: i32,
+        beta: i32,
+    ) -> Option<i32> {
+        if self.should_stop() {
+            return None;
+        }
+
+        self.pv_table.clear(ply);
+        self.seldepth = self.seldepth.max(ply);
+        if ply > 0 {
+            self.nodes_searched += 1;
+            if board.is_draw_by_rule() {
+                return Some(0);
+            }
+        }
+
+        let in_check = board.is_in_check();
+        // Check extension: evasions are searched a ply deeper, so that a series of checks doesn't
+        // push what it leads to past the horizon
+        let depth =
+            if self.config.check_extensions && in_check && ply > 0 && ply < MAX_EXTENSION_PLY {
+                depth + 1
+            } else {
+                depth
+            };
+
+        // Plies are counted on a u8, the quiescence search stops at the last one
+        if depth == 0 || ply >= u8::MAX - 1 {
+            return Some(
+                self.quiescence
+                    .search(board, evaluator.as_ref(), alpha, beta, ply),
+            );
+        }
+
+        // No cutoffs in PV nodes, where the window is open, so that they report a full line
+        let is_pv_node = beta - alpha > 1;
+        let tt_entry = self.tt.probe(board.hash());
+        self.tt_probes += 1;
+        if tt_entry.is_some() {
+            self.tt_hits += 1;
+        }
+        if !is_pv_node
+            && let Some(entry) = tt_entry
+            && entry.depth >= depth
+        {
+            let score = entry.get_score(ply);
+            match entry.bound {
+                Bound::Exact => return Some(score),
+                Bound::Lower if score >= beta => return Some(score),
+                Bound::Upper if score <= alpha => return Some(score),
+                _ => {}
+            }
+        }
+
+        // Pruning relies on the static evaluation, which is meaningless in check
+        let static_eval = (!is_pv_node && !in_check).then(|| clamp_eval(evaluator.evaluate(board)));
+        let pawn_value = evaluator.piece_value(Pawn);
+        if let Some(static_eval) = static_eval {
+            if self.config.reverse_futility_pruning
+                && depth <= REVERSE_FUTILITY_MAX_DEPTH
+                && pawn_value > 0
+                && !is_mate_score(beta)
+            {
+                let margin = REVERSE_FUTILITY_MARGIN * pawn_value / 100 * depth as i32;
+                if static_eval - margin >= beta {
+                    return Some(static_eval - margin);
+                }
+            }
+
+            if self.config.null_move_pruning
+                && depth >= NULL_MOVE_MIN_DEPTH
+                && static_eval >= beta
+                && self.played_moves.last() != Some(&None)
+                && board.has_non_pawn_material(board.get_active_color())
+            {
+                let reduction = NULL_MOVE_REDUCTION + depth / NULL_MOVE_DEPTH_STEP;
+                board.apply_null_move();
+                self.played_moves.push(None);
+                let score = self
+                    .alphabeta(
+                        board,
+                        evaluator,
+                        depth.saturating_sub(1 + reduction),
+                        ply + 1,
+                        -beta,
+                        -beta + 1,
+                    )
+                    .map(|s| -s);
+                self.played_moves.pop();
+                board.undo_null_move();
+                let score = score?; // Propagate stop signal
+                if score >= beta {
+                    // Mates found after passing the turn aren't proven
+                    return Some(if is_mate_score(score) { beta } else { score });
+                }
+            }
+        }
+
+        let mut moves = board.get_legal_moves();
+        if moves.is_empty() {
+            return Some(if in_check { mated_in(ply) } else { 0 });
+        }
+        let tt_move = tt_entry
+            .and_then(|entry| entry.best_move)
+            .and_then(|packed| packed.find_in(&moves));
+        let mut first_move = tt_move;
+        if ply == 0 {
+            // The line of the same rank in the previous iteration is searched first
+            let previous_line_move = self
+                .ranked_moves
+                .get(self.excluded_root_moves.len())
+                .map(|ranked| ranked.mv);
+            moves.retain(|m| !self.excluded_root_moves.contains(m));
+            first_move = previous_line_move.or(tt_move);
+        }
+        let previous_move = self.played_moves.last().copied().flatten();
+        let picker = MovePicker::new(
+            board,
+            moves,
+            first_move,
+            &self.heuristics,
+            ply,
+            previous_move,
+        );
+
+        // Best score a quiet move can reach near the leaves, by the static evaluation
+        let futility_value = static_eval
+            .filter(|_| {
+                self.config.futility_pruning
+                    && depth <= FUTILITY_MAX_DEPTH
+                    && pawn_value > 0
+                    && !is_mate_score(alpha)
+            })
+            .map(|static_eval| static_eval + FUTILITY_MARGIN * pawn_value / 100 * depth as i32);
+        // Least exchange result of the captures searched near the leaves
+        let see_threshold =
+            (self.config.see_pruning && !is_pv_node && !in_check && depth <= SEE_PRUNING_MAX_DEPTH)
+                .then(|| -SEE_PRUNING_MARGIN * depth as i32);
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        // Quiet moves searched without causing a cutoff
+        let mut tried_quiets = Vec::new();
+        for (idx, mv) in picker.enumerate() {
+            if ply == 0 {
+                self.root_move = Some((mv, idx + 1));
+            }
+            let is_quiet = mv.capture.is_none() && mv.promotion.is_none();
+            if idx > 0
+                && !is_quiet
+                && let Some(see_threshold) = see_threshold
+                && !board.see_ge(&mv, see_threshold)
+            {
+                continue;
+            }
+            board.apply_move(&mv);
+            let gives_check = board.is_in_check();
+            if idx > 0
+                && is_quiet
+                && !gives_check
+                && let Some(futility_value) = futility_value
+                && futility_value <= alpha
+            {
+                // Not proven worse, so the optimistic score still bounds the result
+                best_score = best_score.max(futility_value);
+                board.undo_move(&mv);
+                continue;
+            }
+
+            self.played_moves.push(Some(mv));
+            let mut score = if idx == 0 {
+                self.alphabeta(board, evaluator, depth - 1, ply + 1, -beta, -alpha)
+                    .map(|s| -s)
+            } else {
+                // Root moves are all searched to full depth, so that a quiet move starting a
+                // combination isn't missed at the root
+                let reduction = if self.config.late_move_reductions
+                    && ply > 0
+                    && depth >= LMR_MIN_DEPTH
+                    && idx >= LMR_FULL_DEPTH_MOVES
+                    && is_quiet
+                    && !in_check
+                    && !gives_check
+                {
+                    if idx >= LMR_DEEP_MOVES && depth >= LMR_DEEP_MIN_DEPTH {
+                        2
+                    } else {
+                        1
+                    }
+                } else {
+                    0
+                };
+                let mut score = self
+                    .alphabeta(
+                        board,
+                        evaluator,
+                        depth - 1 - reduction,
+                        ply + 1,
+                        -alpha - 1,
+                        -alpha,
+                    )
+                    .map(|s| -s);
+                // A reduced move that beats alpha is verified at full depth
+                if reduction > 0 && score.is_some_and(|s| s > alpha) {
+                    score = self
+                        .alphabeta(board, evaluator, depth - 1, ply + 1, -alpha - 1, -alpha)
+                        .map(|s| -s);
+                }
+                score
+            };
+            if idx > 0 && score.is_some_and(|s| s > alpha && s < beta) {
+                score = self
+                    .alphabeta(board, evaluator, depth - 1, ply + 1, -beta, -alpha)
+                    .map(|s| -s);
+            }
+            self.played_moves.pop();
+            board.undo_move(&mv);
+            let score = score?; // Propagate stop signal
+
+            if score > best_score {
+                best_score = score;
+                if score > alpha {
+                    alpha = score;
+                    best_move = Some(mv);
+                    self.pv_table.update(ply, mv);
+                    if ply == 0 {
+                        self.partial_line = Some((score, self.pv_table.get_pv().to_vec()));
+                    }
+                    if score >= beta {
+                        self.beta_cutoffs += 1;
+                        if idx == 0 {
+                            self.first_move_cutoffs += 1;
+                        }
+                        if is_quiet {
+                            self.heuristics
+                                .update(ply, depth, &mv, &tried_quiets, previous_move);
+                        }
+                        break;
+                    }
+                }
+            }
+            if is_quiet {
+                tried_quiets.push(mv);
+            }
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        // A root searched without some of its moves doesn't have its real score
+        if ply > 0 || self.excluded_root_moves.is_empty() {
+            self.tt
+                .store(board.hash(), depth, bound, best_score, best_move, ply);
+        }
+
+        Some(best_score)
+    }
+
+    /// Searches the root with a window around `previous_score`, the score of the same line in
+    /// the previous iteration, widening the side the score falls out of until it lands inside.
+    /// Scores falling out of the window are reported as bounds when `report_bounds` is set.
+    fn aspiration_search(
+        &mut self,
+        board: &mut Board,
+        evaluator: &Arc<dyn Evaluator>,
+        depth: u8,
+        previous_score: Option<i32>,
+        report_bounds: bool,
+    ) -> Option<i32> {
+        let mut delta = ASPIRATION_WINDOW * evaluator.piece_value(Pawn) / 100;
+        let (mut alpha, mut beta) = match previous_score {
+            Some(score) if depth >= ASPIRATION_MIN_DEPTH && delta > 0 && !is_mate_score(score) => {
+                (score - delta, score + delta)
+            }
+            _ => (-INFINITY, INFINITY),
+        };
+
+        loop {
+            let score = self.alphabeta(board, evaluator, depth, 0, alpha, beta)?;
+            let bound = if score <= alpha {
+                alpha = (score - delta).max(-INFINITY);
+                Bound::Upper
+            } else if score >= beta {
+                beta = (score + delta).min(INFINITY);
+                Bound::Lower
+            } else {
+                return Some(score);
+            };
+            delta *= 2;
+
+            if report_bounds && let Some(callback) = &self.info_callback {
+                // After failing low the root has no line, the previous best one is reported
+                let pv = match bound {
+                    Bound::Lower => self.pv_table.get_pv().to_vec(),
+                    _ => self
+                        .ranked_moves
+                        .first()
+                        .map_or(Vec::new(), |ranked| ranked.pv.clone()),
+                };
+                callback(self.get_info(depth, 1, Score::from_value(score), bound, pv));
+            }
+        }
+    }
+
+    fn get_info(
+        &self,
+        depth: u8,
+        multipv: usize,
+        score: Score,
+        bound: Bound,
+        pv: Vec<Move>,
+    ) -> SearchInfo {
+        SearchInfo {
+            depth,
+            seldepth: self.seldepth.max(self.quiescence.max_ply),
+            multipv,
+            score,
+            bound,
+            nodes: self.get_total_nodes(),
+            qnodes: self.quiescence.nodes_searched,
+            time: self.start_time.elapsed(),
+            pv,
+            hashfull: self.tt.hashfull(),
+            tt_probes: self.tt_probes,
+            tt_hits: self.tt_hits,
+            beta_cutoffs: self.beta_cutoffs,
+            first_move_cutoffs: self.first_move_cutoffs,
+            currmove: None,
+            currmovenumber: 0,
+            progress: false,
+        }
+    }
+
+    /// Reports the root move being searched, with the best line of the last completed iteration.
+    fn report_progress(&mut self) {
+        self.last_progress = Instant::now();
+        if let Some(callback) = &self.info_callback {
+            let (score, pv) = self
+                .ranked_moves
+                .first()
+                .map_or((Score::Centipawns(0), Vec::new()), |ranked| {
+                    (ranked.score, ranked.pv.clone())
+                });
+            let mut info = self.get_info(self.iteration_depth, 1, score, Bound::Exact, pv);
+            if let Some((mv, number)) = self.root_move {
+                info.currmove = Some(mv);
+                info.currmovenumber = number;
+            }
+            info.progress = true;
+            callback(info);
+        }
+    }
+
+    /// Searches `board` with increasing depth until the limits are reached, ranking the root
+    /// moves of each completed iteration.
+    fn iterative_deepening(
+        &mut self,
+        board: &Board,
+        evaluator: &Arc<dyn Evaluator>,
+        first_depth: u8,
+    ) {
+        let mut board_clone = board.clone();
+        let max_depth = self.limits.max_depth.unwrap_or(u8::MAX);
+        let mut time_manager = TimeManager::new(&self.limits);
+
+        let line_count = self.multi_pv.min(board.get_legal_moves().len());
+        // Scores of the lines of the last completed iteration, best first
+        let mut previous_scores = Vec::new();
+
+        // Iterative deepening
+        for depth in first_depth..=max_depth {
+            self.iteration_depth = depth;
+            self.seldepth = 0;
+            self.quiescence.max_ply = 0;
+            // Each line is the best one without the root moves of the lines before it
+            let mut lines = Vec::with_capacity(line_count);
+            self.excluded_root_moves.clear();
+            while lines.len() < line_count {
+                self.partial_line = None;
+                // Only the best line reports its bounds, the others would be taken for new lines
+                let Some(score) = self.aspiration_search(
+                    &mut board_clone,
+                    evaluator,
+                    depth,
+                    previous_scores.get(lines.len()).copied(),
+                    lines.is_empty(),
+                ) else {
+                    break;
+                };
+                let pv = self.pv_table.get_pv().to_vec();
+                debug_assert!(is_legal_line(board, &pv), "illegal PV at depth {}", depth);
+                self.excluded_root_moves.push(pv[0]);
+                lines.push((score, pv));
+            }
+            self.excluded_root_moves.clear();
+            if lines.len() < line_count {
+                // The best move of the interrupted iteration is at least as good as the one of
+                // the previous iteration, searched first, but the other root moves weren't all
+                // searched so its score is a lower bound
+                if line_count == 1
+                    && let Some((score, pv)) = self.partial_line.take()
+                {
+                    debug_assert!(is_legal_line(board, &pv), "illegal PV at depth {}", depth);
+                    let score = Score::from_value(score);
+                    if let Some(callback) = &self.info_callback {
+                        callback(self.get_info(depth, 1, score, Bound::Lower, pv.clone()));
+                    }
+                    self.ranked_moves = vec![RankedMove {
+                        mv: pv[0],
+                        score,
+                        pv,
+                    }];
+                }
+                break;
+            }
+
+            // Scores of later lines can come out higher, as they are searched with more of the
+            // tree in the transposition table
+            lines.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+            previous_scores = lines.iter().map(|(score, _)| *score).collect();
+            self.ranked_moves = lines
+                .into_iter()
+                .map(|(score, pv)| RankedMove {
+                    mv: pv[0],
+                    score: Score::from_value(score),
+                    pv,
+                })
+                .collect();
+
+            if let Some(callback) = &self.info_callback {
+                for (idx, ranked) in self.ranked_moves.iter().enumerate() {
+                    callback(self.get_info(
+                        depth,
+                        idx + 1,
+                        ranked.score,
+                        Bound::Exact,
+                        ranked.pv.clone(),
+                    ));
+                }
+            }
+
+            if time_manager.iteration_done(
+                self.ranked_moves.first().map(|ranked| ranked.mv),
+                self.limits.get_clock_elapsed(&self.start_time),
+            ) {
+                break;
+            }
+        }
+    }
+}
+
+impl SearchAlgorithm for AlphaBetaSearch {
+    fn search(
+        &mut self,
+        board: &Board,
+        evaluator: Arc<dyn Evaluator>,
+        limits: SearchLimits,
+        stop_flag: Arc<AtomicBool>,
+        info_callback: Option<Box<dyn Fn(SearchInfo) + Send>>,
+    ) -> Option<Move> {
+        self.reset(limits, stop_flag);
+        self.tt.new_search();
+
+        let moves = board.get_legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+
+        self.info_callback = info_callback;
+        if !self.helpers.is_empty() {
+            let helpers_stop = Arc::new(AtomicBool::new(false));
+            let mut helpers = std::mem::take(&mut self.helpers);
+            for helper in &mut helpers {
+                helper.reset_helper(self, Arc::clone(&helpers_stop));
+            }
+            thread::scope(|scope| {
+                for (idx, helper) in helpers.iter_mut().enumerate() {
+                    let evaluator = Arc::clone(&evaluator);
+                    // Half the helpers start one iteration ahead, so the threads don't all
+                    // search the same depth at the same time
+                    let first_depth = 1 + ((idx + 1) % 2) as u8;
+                    scope.spawn(move || helper.iterative_deepening(board, &evaluator, first_depth));
+                }
+                self.iterative_deepening(board, &evaluator, 1);
+                helpers_stop.store(true, Ordering::Relaxed);
+            });
+            self.helpers = helpers;
+        } else {
+            self.iterative_deepening(board, &evaluator, 1);
+        }
+        self.info_callback = None;
+
+        self.ranked_moves
+            .first()
+            .map(|ranked| ranked.mv)
+            .or(Some(moves[0]))
+    }
+
+    fn set_hash_size(&mut self, size_mb: usize) {
+        self.tt = Arc::new(TranspositionTable::new(size_mb));
+        for helper in &mut self.helpers {
+            helper.tt = Arc::clone(&self.tt);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.tt.clear();
+        self.heuristics.clear();
+        for helper in &mut self.helpers {
+            helper.heuristics.clear();
+        }
+    }
+
+    fn set_multi_pv(&mut self, count: usize) {
+        self.multi_pv = count.max(1);
+    }
+
+    fn set_threads(&mut self, count: usize) {
+        self.threads = count.max(1);
+        // Built here rather than in search, where the clock would already be running
+        self.helpers.truncate(self.threads - 1);
+        while self.helpers.len() < self.threads - 1 {
+            let helper = Self::new_helper(self);
+            self.helpers.push(helper);
+        }
+    }
+
+    fn set_config(&mut self, config: SearchConfig) {
+        self.config = config;
+    }
+
+    fn get_ranked_moves(&self) -> Vec<RankedMove> {
+        self.ranked_moves.clone()
+    }
+}
+
+impl Default for AlphaBetaSearch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluate::{MaterialEvaluator, PositioningEvaluator, SumEvaluator};
+    use crate::search::MinimaxSearch;
+    use std::sync::OnceLock;
+    use std::time::Duration;
+
+    fn evaluator() -> Arc<dyn Evaluator> {
+        Arc::new(SumEvaluator::new(vec![
+            Box::new(MaterialEvaluator::new(10)),
+            Box::new(PositioningEvaluator::new(1)),
+        ]))
+    }
+
+    /// Returns the info reported for the last iteration.
+    fn search_info(search: &mut dyn SearchAlgorithm, board: &Board, depth: u8) -> SearchInfo {
+        let limits = SearchLimits {
+            max_depth: Some(depth),
+            ..Default::default()
+        };
+        search_info_with_limits(search, board, limits)
+    }
+
+    /// Last info reported within `limits`, which can be of an interrupted iteration.
+    fn search_info_with_limits(
+        search: &mut dyn SearchAlgorithm,
+        board: &Board,
+        limits: SearchLimits,
+    ) -> SearchInfo {
+        let info = Arc::new(std::sync::Mutex::new(None));
+        let info_clone = Arc::clone(&info);
+        search.search(
+            board,
+            evaluator(),
+            limits,
+            Arc::new(AtomicBool::new(false)),
+            Some(Box::new(move |info: SearchInfo| {
+                if !info.progress {
+                    *info_clone.lock().unwrap() = Some(info);
+                }
+            })),
+        );
+        info.lock().unwrap().take().unwrap()
+    }
+
+    /// All infos reported by a search of `board` within `limits`.
+    fn search_infos(board: &Board, limits: SearchLimits) -> (Option<Move>, Vec<SearchInfo>) {
+        let infos = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let infos_clone = Arc::clone(&infos);
+        let best_move = AlphaBetaSearch::new().search(
+            board,
+            evaluator(),
+            limits,
+            Arc::new(AtomicBool::new(false)),
+            Some(Box::new(move |info: SearchInfo| {
+                infos_clone.lock().unwrap().push(info);
+            })),
+        );
+        let infos = infos.lock().unwrap().clone();
+        (best_move, infos)
+    }
+
+    /// Alpha-beta without selective search, which scores positions exactly like minimax.
+    fn plain_alphabeta() -> AlphaBetaSearch {
+        let mut search = AlphaBetaSearch::new();
+        search.set_config(SearchConfig::DISABLED);
+        search
+    }
+
+    #[test]
+    fn test_same_score_as_minimax() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r1bqk2r/ppp1nppp/4p3/n5N1/2BPp3/P1P5/2P2PPP/R1BQK2R w KQkq - 0 1",
+            "6k1/1b1nqpbp/pp4p1/5P2/1PN5/4Q3/P5PP/1B2B1K1 b - - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ] {
+            let board = Board::from_fen(fen);
+            assert_eq!(
+                search_info(&mut plain_alphabeta(), &board, 2).score,
+                search_info(&mut MinimaxSearch::new(), &board, 2).score,
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn test_pruning_reduces_nodes() {
+        let board =
+            Board::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4");
+        let plain = search_info(&mut plain_alphabeta(), &board, 4).nodes;
+        let disabled = SearchConfig::DISABLED;
+        for config in [
+            SearchConfig {
+                null_move_pruning: true,
+                ..disabled
+            },
+            SearchConfig {
+                late_move_reductions: true,
+                ..disabled
+            },
+            SearchConfig {
+                reverse_futility_pruning: true,
+                ..disabled
+            },
+            SearchConfig {
+                futility_pruning: true,
+                ..disabled
+            },
+            SearchConfig {
+                see_pruning: true,
+                ..disabled
+            },
+            SearchConfig::default(),
+        ] {
+            let mut search = AlphaBetaSearch::new();
+            search.set_config(config);
+            let nodes = search_info(&mut search, &board, 4).nodes;
+            assert!(
+                nodes < plain,
+                "{:?}: {} nodes, {} without",
+                config,
+                nodes,
+                plain
+            );
+        }
+    }
+
+    #[test]
+    fn test_multi_pv_ranks_root_moves_like_minimax() {
+        let limits = SearchLimits {
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        for fen in [
+            "r1bqk2r/ppp1nppp/4p3/n5N1/2BPp3/P1P5/2P2PPP/R1BQK2R w KQkq - 0 1",
+            "6k1/1b1nqpbp/pp4p1/5P2/1PN5/4Q3/P5PP/1B2B1K1 b - - 0 1",
+        ] {
+            let board = Board::from_fen(fen);
+            let mut alphabeta = plain_alphabeta();
+            let mut minimax = MinimaxSearch::new();
+            alphabeta.set_multi_pv(3);
+            minimax.set_multi_pv(3);
+            let best_move = alphabeta.search_simple(&board, evaluator(), limits.clone());
+            minimax.search_simple(&board, evaluator(), limits.clone());
+
+            let ranked = alphabeta.get_ranked_moves();
+            assert_eq!(ranked.len(), 3);
+            assert_eq!(best_move, Some(ranked[0].mv));
+            for (i, line) in ranked.iter().enumerate() {
+                assert_eq!(line.pv[0], line.mv);
+                assert!(ranked[..i].iter().all(|other| other.mv != line.mv));
+            }
+            let scores = |ranked: Vec<RankedMove>| -> Vec<Score> {
+                ranked.into_iter().map(|line| line.score).collect()
+            };
+            assert_eq!(
+                scores(ranked),
+                scores(minimax.get_ranked_moves()),
+                "{}",
+                fen
+            );
+        }
+
+        // there can't be more lines than legal moves
+        let board = Board::from_fen("7k/8/8/8/8/8/8/K7 w - - 0 1");
+        let mut search = AlphaBetaSearch::new();
+        search.set_multi_pv(5);
+        search.search_simple(&board, evaluator(), limits);
+        assert_eq!(search.get_ranked_moves().len(), 3);
+    }
+
+    #[test]
+    fn test_transposition_table_persists_between_searches() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let mut search = AlphaBetaSearch::new();
+        let first = search_info(&mut search, &board, 5);
+        let second = search_info(&mut search, &board, 5);
+        assert_eq!(first.score, second.score);
+        assert!(second.nodes < first.nodes / 4);
+
+        search.clear();
+        assert_eq!(search_info(&mut search, &board, 5).nodes, first.nodes);
+    }
+
+    #[test]
+    fn test_respects_time_limits() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let depths = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let depths_clone = Arc::clone(&depths);
+        let mut search = AlphaBetaSearch::new();
+        // the soft limit lets the first iteration finish, but no other one start
+        let best_move = search.search(
+            &board,
+            evaluator(),
+            SearchLimits {
+                soft_time: Some(Duration::ZERO),
+                ..Default::default()
+            },
+            Arc::new(AtomicBool::new(false)),
+            Some(Box::new(move |info: SearchInfo| {
+                depths_clone.lock().unwrap().push(info.depth);
+            })),
+        );
+        assert!(best_move.is_some());
+        assert_eq!(*depths.lock().unwrap(), vec![1]);
+
+        // the hard limit aborts the search inside the tree
+        let start = Instant::now();
+        let best_move = search.search_simple(
+            &board,
+            evaluator(),
+            SearchLimits {
+                max_time: Some(Duration::from_millis(50)),
+                ..Default::default()
+            },
+        );
+        assert!(best_move.is_some());
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_time_limits_wait_for_ponderhit() {
+        let board = Board::default();
+        let ponderhit = Arc::new(OnceLock::new());
+        let limits = SearchLimits {
+            max_depth: Some(3),
+            max_time: Some(Duration::ZERO),
+            soft_time: Some(Duration::ZERO),
+            ponderhit: Some(Arc::clone(&ponderhit)),
+            ..Default::default()
+        };
+        let info = search_info_with_limits(&mut AlphaBetaSearch::new(), &board, limits.clone());
+        assert_eq!(info.depth, 3);
+
+        ponderhit.set(Instant::now()).unwrap();
+        let info = search_info_with_limits(&mut AlphaBetaSearch::new(), &board, limits);
+        assert_eq!(info.depth, 1);
+    }
+
+    #[test]
+    fn test_reports_full_legal_pv() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r1bqk2r/ppp1nppp/4p3/n5N1/2BPp3/P1P5/2P2PPP/R1BQK2R w KQkq - 0 1",
+        ] {
+            let board = Board::from_fen(fen);
+            for depth in 1..=4 {
+                let pv = search_info(&mut AlphaBetaSearch::new(), &board, depth).pv;
+                // check extensions can make it longer
+                assert!(pv.len() >= depth as usize, "{} at depth {}", fen, depth);
+                assert!(is_legal_line(&board, &pv), "{} at depth {}", fen, depth);
+            }
+            let pv = search_info(&mut MinimaxSearch::new(), &board, 2).pv;
+            assert_eq!(pv.len(), 2);
+            assert!(is_legal_line(&board, &pv));
+        }
+    }
+
+    #[test]
+    fn test_single_thread_is_deterministic() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let first = search_info(&mut AlphaBetaSearch::new(), &board, 4);
+        let mut search = AlphaBetaSearch::new();
+        search.set_threads(1);
+        let second = search_info(&mut search, &board, 4);
+        assert_eq!(first.nodes, second.nodes);
+        assert_eq!(first.pv, second.pv);
+    }
+
+    #[test]
+    fn test_parallel_search() {
+        let board =
+            Board::from_fen("r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1");
+        let mut search = AlphaBetaSearch::new();
+        search.set_threads(4);
+        let info = search_info(&mut search, &board, 4);
+        assert_eq!(info.score, Score::Mate(2));
+        assert_eq!(info.pv[0].to_uci_notation(), "d5f6");
+        assert!(is_legal_line(&board, &info.pv));
+
+        // the helper threads stop with the main one
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let stop_flag_clone = Arc::clone(&stop_flag);
+        let handle = thread::spawn(move || {
+            search.search(
+                &Board::default(),
+                evaluator(),
+                SearchLimits::default(),
+                stop_flag_clone,
+                None,
+            )
+        });
+        thread::sleep(Duration::from_millis(50));
+        stop_flag.store(true, Ordering::Relaxed);
+        assert!(handle.join().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_helpers_share_the_table() {
+        let mut search = AlphaBetaSearch::new();
+        search.set_threads(4);
+        search.set_hash_size(1);
+        assert_eq!(search.helpers.len(), 3);
+        assert!(
+            search
+                .helpers
+                .iter()
+                .all(|helper| Arc::ptr_eq(&helper.tt, &search.tt))
+        );
+        search.set_threads(2);
+        assert_eq!(search.helpers.len(), 1);
+        assert!(Arc::ptr_eq(&search.helpers[0].tt, &search.tt));
+    }
+
+    #[test]
+    fn test_stops_at_max_ply() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/3Q4/4K3 w - - 0 1");
+        let mut search = AlphaBetaSearch::new();
+        search.reset(SearchLimits::default(), Arc::new(AtomicBool::new(false)));
+        let score = search.alphabeta(
+            &mut board,
+            &evaluator(),
+            8,
+            u8::MAX - 3,
+            -INFINITY,
+            INFINITY,
+        );
+        assert!(score.is_some_and(|score| !is_mate_score(score)));
+    }
+
+    #[test]
+    fn test_finds_mate_in_two() {
+        let board =
+            Board::from_fen("r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1");
+        let best_move = AlphaBetaSearch::new()
+            .search_simple(
+                &board,
+                evaluator(),
+                SearchLimits {
+                    max_depth: Some(4),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(best_move.to_uci_notation(), "d5f6");
+        assert_eq!(
+            search_info(&mut AlphaBetaSearch::new(), &board, 4).score,
+            Score::Mate(2)
+        );
+
+        // the side getting mated sees a negative mate, counted in its own moves
+        let mut board = board;
+        board.apply_move(&best_move);
+        assert_eq!(
+            search_info(&mut AlphaBetaSearch::new(), &board, 3).score,
+            Score::Mate(-1)
+        );
+    }
+
+    #[test]
+    fn test_aspiration_windows_report_bounds() {
+        // the score jumps at depth 4 and falls back at depth 5
+        let board = Board::from_fen("r4rk1/ppp2ppp/2n5/2bqp3/8/P2PB3/1PP1NPPP/R2Q1RK1 w - - 0 1");
+        let (_, infos) = search_infos(
+            &board,
+            SearchLimits {
+                max_depth: Some(5),
+                ..Default::default()
+            },
+        );
+        assert!(infos.iter().any(|info| info.bound == Bound::Lower));
+        assert!(infos.iter().any(|info| info.bound == Bound::Upper));
+        // every iteration ends with an exact score
+        for (info, next) in infos.iter().zip(infos.iter().skip(1)) {
+            if next.depth > info.depth {
+                assert_eq!(info.bound, Bound::Exact);
+            }
+        }
+        assert_eq!(infos.last().unwrap().bound, Bound::Exact);
+    }
+
+    #[test]
+    fn test_keeps_best_move_of_interrupted_iteration() {
+        let board =
+            Board::from_fen("r1bqk2r/ppp1nppp/4p3/n5N1/2BPp3/P1P5/2P2PPP/R1BQK2R w KQkq - 0 1");
+        let (best_move, infos) = search_infos(
+            &board,
+            SearchLimits {
+                max_nodes: Some(20000),
+                ..Default::default()
+            },
+        );
+        let infos: Vec<_> = infos.into_iter().filter(|info| !info.progress).collect();
+        let last = infos.last().unwrap();
+        let completed = &infos[infos.len() - 2];
+        assert_eq!(last.bound, Bound::Lower);
+        assert_eq!(last.depth, completed.depth + 1);
+        assert_eq!(completed.bound, Bound::Exact);
+        assert_eq!(best_move, Some(last.pv[0]));
+    }
+
+    #[test]
+    fn test_reports_search_statistics() {
+        let board =
+            Board::from_fen("r1bqk2r/ppp1nppp/4p3/n5N1/2BPp3/P1P5/2P2PPP/R1BQK2R w KQkq - 0 1");
+        let info = search_info(&mut AlphaBetaSearch::new(), &board, 5);
+        // quiescence search goes past the nominal depth
+        assert!(info.seldepth > info.depth);
+        assert!(info.qnodes > 0 && info.qnodes < info.nodes);
+        assert!(info.tt_hits > 0 && info.tt_hits < info.tt_probes);
+        assert!(info.first_move_cutoffs <= info.beta_cutoffs);
+        assert!(info.hashfull > 0);
+        assert_eq!(info.currmove, None);
+    }
+
+    #[test]
+    fn test_reports_progress() {
+        let board = Board::default();
+        let (_, infos) = search_infos(
+            &board,
+            SearchLimits {
+                max_time: Some(Duration::from_millis(1500)),
+                ..Default::default()
+            },
+        );
+        let progress = infos
+            .iter()
+            .find(|info| info.progress)
+            .expect("no progress report");
+        let currmove = progress.currmove.unwrap();
+        assert!(board.get_legal_moves().contains(&currmove));
+        assert!(progress.currmovenumber >= 1);
+        assert!(progress.time >= PROGRESS_INTERVAL);
+    }
+}
@@ -0,0 +1,48 @@
+/// Selective search techniques of [`super::AlphaBetaSearch`], each of which can be turned off to
+/// measure its effect. All are enabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchConfig {
+    /// Null-move pruning: when passing the turn still fails high at a reduced depth, the node is
+    /// assumed to fail high. Not done in check, nor when the side to move only has pawns left,
+    /// where zugzwang is common.
+    pub null_move_pruning: bool,
+    /// Late move reductions: quiet moves ordered late are searched at a reduced depth first, and
+    /// only at full depth if they beat alpha.
+    pub late_move_reductions: bool,
+    /// Reverse futility pruning: near the leaves, a node whose static evaluation beats beta by a
+    /// margin growing with depth fails high without searching.
+    pub reverse_futility_pruning: bool,
+    /// Futility pruning: near the leaves, quiet moves are skipped when the static evaluation is
+    /// too far below alpha for them to raise it.
+    pub futility_pruning: bool,
+    /// SEE pruning: near the leaves, captures losing too much material by static exchange
+    /// evaluation are skipped.
+    pub see_pruning: bool,
+    /// Check extensions: positions in check are searched one ply deeper.
+    pub check_extensions: bool,
+}
+
+impl SearchConfig {
+    /// Plain alpha-beta, searching every move to the full depth.
+    pub const DISABLED: SearchConfig = SearchConfig {
+        null_move_pruning: false,
+        late_move_reductions: false,
+        reverse_futility_pruning: false,
+        futility_pruning: false,
+        see_pruning: false,
+        check_extensions: false,
+    };
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            null_move_pruning: true,
+            late_move_reductions: true,
+            reverse_futility_pruning: true,
+            futility_pruning: true,
+            see_pruning: true,
+            check_extensions: true,
+        }
+    }
+}
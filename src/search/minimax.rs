@@ -1,20 +1,59 @@
-use std::cmp;
 use std::sync::{
     Arc,
     atomic::{AtomicBool, Ordering},
 };
 use std::time::Instant;
 
-use super::{SearchAlgorithm, SearchInfo, SearchLimits};
+use super::{
+    Bound, LIMITS_CHECK_INTERVAL, MATE_SCORE, QuiescenceSearch, RankedMove, Score, SearchAlgorithm,
+    SearchInfo, SearchLimits, TimeManager, mated_in,
+    pv::{PvTable, is_legal_line},
+};
 use crate::{Board, evaluate::Evaluator, r#move::Move};
 
 pub struct MinimaxSearch {
     nodes_searched: usize,
+    quiescence: QuiescenceSearch,
+    pv_table: PvTable,
+    last_limits_check: usize,
+    limits: SearchLimits,
+    start_time: Instant,
+    stop_flag: Arc<AtomicBool>,
+    is_stopped: bool,
+    multi_pv: usize,
+    // Best root moves of the last completed iteration
+    ranked_moves: Vec<RankedMove>,
 }
 
 impl MinimaxSearch {
     pub fn new() -> Self {
-        Self { nodes_searched: 0 }
+        Self {
+            nodes_searched: 0,
+            quiescence: QuiescenceSearch::new(),
+            pv_table: PvTable::new(),
+            last_limits_check: 0,
+            limits: SearchLimits::default(),
+            start_time: Instant::now(),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            is_stopped: false,
+            multi_pv: 1,
+            ranked_moves: Vec::new(),
+        }
+    }
+
+    /// Nodes searched so far, quiescence included.
+    fn get_nodes(&self) -> usize {
+        self.nodes_searched + self.quiescence.nodes_searched
+    }
+
+    fn should_stop(&mut self) -> bool {
+        let nodes = self.get_nodes();
+        if !self.is_stopped && nodes - self.last_limits_check >= LIMITS_CHECK_INTERVAL {
+            self.last_limits_check = nodes;
+            self.is_stopped =
+                super::should_stop(&self.limits, &self.start_time, nodes, &self.stop_flag);
+        }
+        self.is_stopped || self.stop_flag.load(Ordering::Relaxed)
     }
 
     fn minimax(
@@ -22,27 +61,42 @@ impl MinimaxSearch {
         board: &mut Board,
         evaluator: &Arc<dyn Evaluator>,
         depth: u8,
-        stop_flag: &Arc<AtomicBool>,
+        ply: u8,
     ) -> Option<i32> {
-        if stop_flag.load(Ordering::Relaxed) {
+        if self.should_stop() {
             return None;
         }
 
         self.nodes_searched += 1;
+        self.pv_table.clear(ply);
+
+        if board.is_draw_by_rule() {
+            return Some(0);
+        }
 
         if depth == 0 {
-            return Some(evaluator.evaluate(board));
+            return Some(self.quiescence.search(
+                board,
+                evaluator.as_ref(),
+                -MATE_SCORE,
+                MATE_SCORE,
+                ply,
+            ));
         }
 
         let moves = board.get_legal_moves();
         if moves.is_empty() {
-            return Some(if board.is_in_check() { -100000 } else { 0 });
+            return Some(if board.is_in_check() {
+                mated_in(ply)
+            } else {
+                0
+            });
         }
 
         let mut best_score = i32::MIN;
         for mv in moves {
             board.apply_move(&mv);
-            let score = match self.minimax(board, evaluator, depth - 1, stop_flag) {
+            let score = match self.minimax(board, evaluator, depth - 1, ply + 1) {
                 Some(s) => -s,
                 None => {
                     board.undo_move(&mv);
@@ -51,7 +105,10 @@ impl MinimaxSearch {
             };
             board.undo_move(&mv);
 
-            best_score = cmp::max(best_score, score);
+            if score > best_score {
+                best_score = score;
+                self.pv_table.update(ply, mv);
+            }
         }
 
         Some(best_score)
@@ -67,71 +124,105 @@ impl SearchAlgorithm for MinimaxSearch {
         stop_flag: Arc<AtomicBool>,
         info_callback: Option<Box<dyn Fn(SearchInfo) + Send>>,
     ) -> Option<Move> {
-        let start_time = Instant::now();
+        self.start_time = Instant::now();
         self.nodes_searched = 0;
+        self.quiescence.nodes_searched = 0;
+        self.last_limits_check = 0;
+        self.limits = limits;
+        self.stop_flag = stop_flag;
+        self.is_stopped = false;
 
         let moves = board.get_legal_moves();
         if moves.is_empty() {
             return None;
         }
 
-        let mut is_stopped = false;
         let mut board_clone = board.clone();
-        let mut best_move = moves[0];
-        let mut best_score;
-        let mut current_depth = 1;
+        let mut time_manager = TimeManager::new(&self.limits);
+        self.ranked_moves.clear();
 
         // Iterative deepening
-        while !is_stopped && current_depth <= limits.max_depth.unwrap_or(u8::MAX) {
-            let mut depth_best_move = moves[0];
-            let mut depth_best_score = i32::MIN;
-
+        for current_depth in 1..=self.limits.max_depth.unwrap_or(u8::MAX) {
+            self.quiescence.max_ply = 0;
+            // Every root move gets an exact score, so ranking them is just sorting
+            let mut lines = Vec::with_capacity(moves.len());
             for mv in &moves {
                 board_clone.apply_move(mv);
-                let score =
-                    match self.minimax(&mut board_clone, &evaluator, current_depth - 1, &stop_flag)
-                    {
-                        Some(s) => -s,
-                        None => {
-                            board_clone.undo_move(mv);
-                            break;
-                        }
-                    };
+                let score = self.minimax(&mut board_clone, &evaluator, current_depth - 1, 1);
+                let mut pv = vec![*mv];
+                pv.extend_from_slice(self.pv_table.get_line(1));
                 board_clone.undo_move(mv);
-
-                if score > depth_best_score {
-                    depth_best_score = score;
-                    depth_best_move = *mv;
-                }
-
-                // TODO consider adding this check inside minimax to be more accurate
-                if super::should_stop(&limits, &start_time, self.nodes_searched, &stop_flag) {
-                    is_stopped = true;
+                // Stopped in the middle of the iteration, its result is incomplete
+                let Some(score) = score.map(|s| -s) else {
                     break;
-                }
+                };
+                lines.push((score, pv));
+            }
+            if lines.len() < moves.len() {
+                break;
             }
 
-            // Update best move from completed depth
-            if !stop_flag.load(Ordering::Relaxed) {
-                best_move = depth_best_move;
-                best_score = depth_best_score;
-
-                // Send info update
-                if let Some(ref callback) = info_callback {
+            // Stable, so the first of equally scored moves stays the best one
+            lines.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+            lines.truncate(self.multi_pv);
+            self.ranked_moves = lines
+                .into_iter()
+                .map(|(score, pv)| RankedMove {
+                    mv: pv[0],
+                    score: Score::from_value(score),
+                    pv,
+                })
+                .collect();
+
+            if let Some(ref callback) = info_callback {
+                for (idx, ranked) in self.ranked_moves.iter().enumerate() {
+                    debug_assert!(
+                        is_legal_line(board, &ranked.pv),
+                        "illegal PV at depth {}",
+                        current_depth
+                    );
                     callback(SearchInfo {
                         depth: current_depth,
-                        score: best_score,
-                        nodes: self.nodes_searched,
-                        time: start_time.elapsed(),
-                        pv: vec![best_move], // Collect PV?
+                        seldepth: self.quiescence.max_ply.max(current_depth),
+                        multipv: idx + 1,
+                        score: ranked.score,
+                        bound: Bound::Exact,
+                        nodes: self.get_nodes(),
+                        qnodes: self.quiescence.nodes_searched,
+                        time: self.start_time.elapsed(),
+                        pv: ranked.pv.clone(),
+                        hashfull: 0,
+                        tt_probes: 0,
+                        tt_hits: 0,
+                        beta_cutoffs: 0,
+                        first_move_cutoffs: 0,
+                        currmove: None,
+                        currmovenumber: 0,
+                        progress: false,
                     });
                 }
             }
 
-            current_depth += 1;
+            if time_manager.iteration_done(
+                self.ranked_moves.first().map(|ranked| ranked.mv),
+                self.limits.get_clock_elapsed(&self.start_time),
+            ) {
+                break;
+            }
         }
 
-        Some(best_move)
+        self.ranked_moves
+            .first()
+            .map(|ranked| ranked.mv)
+            .or(Some(moves[0]))
+    }
+
+    fn set_multi_pv(&mut self, count: usize) {
+        self.multi_pv = count.max(1);
+    }
+
+    fn get_ranked_moves(&self) -> Vec<RankedMove> {
+        self.ranked_moves.clone()
     }
 }
 
@@ -0,0 +1,266 @@
+use std::cmp::Reverse;
+
+use crate::{Board, board::piece::PieceKind, r#move::Move};
+
+/// Bound of the history scores, which approach it more slowly the closer they get.
+const MAX_HISTORY: i32 = 16384;
+
+// Keys of the stages of the move picker, higher is picked earlier. Quiet moves without a stage
+// of their own are keyed by their history score, between the countermove and bad captures.
+const FIRST_MOVE_KEY: i32 = i32::MAX;
+const GOOD_CAPTURE_KEY: i32 = 3_000_000;
+const KILLER_KEY: i32 = 2_000_000;
+const COUNTERMOVE_KEY: i32 = 1_000_000;
+const BAD_CAPTURE_KEY: i32 = -1_000_000;
+
+fn piece_value(kind: PieceKind) -> i32 {
+    match kind {
+        PieceKind::Pawn => 1,
+        PieceKind::Knight => 3,
+        PieceKind::Bishop => 3,
+        PieceKind::Rook => 5,
+        PieceKind::Queen => 9,
+        PieceKind::King => 100,
+    }
+}
+
+/// Ordering score of a move, higher is searched earlier. Promotions come first, then captures
+/// ordered by MVV-LVA (most valuable victim, then least valuable attacker), then quiet moves.
+pub fn score_move(m: &Move) -> i32 {
+    let mut score = 0;
+    if let Some(promotion) = m.promotion {
+        score += 1000 + 100 * piece_value(promotion.get_kind());
+    }
+    if let Some(capture) = m.capture {
+        score += 100 + 10 * piece_value(capture.get_kind()) - piece_value(m.piece.get_kind());
+    }
+    score
+}
+
+fn is_quiet(m: &Move) -> bool {
+    m.capture.is_none() && m.promotion.is_none()
+}
+
+/// Sorts moves by [`score_move`], putting `first` (e.g. the best move of a previous iteration)
+/// in front of everything else.
+pub fn order_moves(moves: &mut [Move], first: Option<Move>) {
+    moves.sort_by_cached_key(|m| {
+        Reverse(if Some(*m) == first {
+            i32::MAX
+        } else {
+            score_move(m)
+        })
+    });
+}
+
+/// Quiet move ordering learned from the beta cutoffs of a search: killer moves, butterfly history
+/// and countermoves.
+pub struct OrderingHeuristics {
+    // The last two quiet moves that caused a cutoff at each ply
+    killers: Vec<[Option<Move>; 2]>,
+    // Score of quiet moves by color, origin and destination, raised when they cause a cutoff and
+    // lowered when they were searched before the move that did
+    history: Box<[[[i32; 64]; 64]; 2]>,
+    // Quiet move that refuted a move, by the color and kind of the moved piece and its destination
+    countermoves: Box<[[[Option<Move>; 64]; 6]; 2]>,
+}
+
+impl OrderingHeuristics {
+    pub fn new() -> Self {
+        Self {
+            killers: Vec::new(),
+            history: Box::new([[[0; 64]; 64]; 2]),
+            countermoves: Box::new([[[None; 64]; 6]; 2]),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Forgets the killers, which belong to the positions of the previous search, and ages the
+    /// history so that new cutoffs weigh more.
+    pub fn new_search(&mut self) {
+        self.killers.clear();
+        self.history
+            .iter_mut()
+            .flatten()
+            .flatten()
+            .for_each(|score| *score /= 2);
+    }
+
+    fn get_killers(&self, ply: u8) -> [Option<Move>; 2] {
+        self.killers.get(ply as usize).copied().unwrap_or_default()
+    }
+
+    fn get_history(&self, m: &Move) -> i32 {
+        self.history[m.piece.get_color().index()][m.from.to_index()][m.to.to_index()]
+    }
+
+    fn get_countermove(&self, previous_move: Option<Move>) -> Option<Move> {
+        let previous = previous_move?;
+        self.countermoves[previous.piece.get_color().index()][previous.piece.get_kind().index()]
+            [previous.to.to_index()]
+    }
+
+    /// Records that the quiet move `best` caused a beta cutoff at `ply`, after the quiet moves
+    /// `tried` failed to. `previous_move` is the move that led to the node, if not a null move.
+    pub fn update(
+        &mut self,
+        ply: u8,
+        depth: u8,
+        best: &Move,
+        tried: &[Move],
+        previous_move: Option<Move>,
+    ) {
+        let ply = ply as usize;
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None; 2]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(*best) {
+            killers[1] = killers[0];
+            killers[0] = Some(*best);
+        }
+
+        let bonus = (depth as i32 * depth as i32).min(MAX_HISTORY);
+        self.add_history(best, bonus);
+        for m in tried {
+            self.add_history(m, -bonus);
+        }
+
+        if let Some(previous) = previous_move {
+            self.countermoves[previous.piece.get_color().index()]
+                [previous.piece.get_kind().index()][previous.to.to_index()] = Some(*best);
+        }
+    }
+
+    fn add_history(&mut self, m: &Move, bonus: i32) {
+        let score =
+            &mut self.history[m.piece.get_color().index()][m.from.to_index()][m.to.to_index()];
+        // Scores close to the bound change less, so that they stay within it
+        *score += bonus - *score * bonus.abs() / MAX_HISTORY;
+    }
+}
+
+impl Default for OrderingHeuristics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Hands out the moves of a node in stages: the move to search first (e.g. from the transposition
+/// table), good captures and promotions by MVV-LVA, the killers, the countermove, the other quiet
+/// moves by history, and the bad captures by MVV-LVA. Moves are only picked when needed, so
+/// nothing is sorted past a beta cutoff.
+pub struct MovePicker {
+    moves: Vec<(i32, Move)>,
+    picked: usize,
+}
+
+impl MovePicker {
+    /// Picks `moves` of `board`. `previous_move` is the move that led to it, if not a null move.
+    pub fn new(
+        board: &Board,
+        moves: Vec<Move>,
+        first: Option<Move>,
+        heuristics: &OrderingHeuristics,
+        ply: u8,
+        previous_move: Option<Move>,
+    ) -> Self {
+        let killers = heuristics.get_killers(ply);
+        let countermove = heuristics.get_countermove(previous_move);
+        let key = |m: &Move| {
+            if Some(*m) == first {
+                FIRST_MOVE_KEY
+            } else if !is_quiet(m) {
+                let base = if board.see_ge(m, 0) {
+                    GOOD_CAPTURE_KEY
+                } else {
+                    BAD_CAPTURE_KEY
+                };
+                base + score_move(m)
+            } else if let Some(idx) = killers.iter().position(|killer| *killer == Some(*m)) {
+                KILLER_KEY - idx as i32
+            } else if countermove == Some(*m) {
+                COUNTERMOVE_KEY
+            } else {
+                heuristics.get_history(m)
+            }
+        };
+        Self {
+            moves: moves.into_iter().map(|m| (key(&m), m)).collect(),
+            picked: 0,
+        }
+    }
+}
+
+impl Iterator for MovePicker {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        let remaining = &mut self.moves[self.picked..];
+        let best = (0..remaining.len()).max_by_key(|&idx| (remaining[idx].0, Reverse(idx)))?;
+        remaining.swap(0, best);
+        self.picked += 1;
+        Some(remaining[0].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_promotions_then_mvv_lva() {
+        let board = Board::from_fen("4k3/1P6/8/2r1n3/3P4/Q7/8/4K3 w - - 0 1");
+        let mut moves = board.get_legal_moves();
+        order_moves(&mut moves, None);
+
+        assert_eq!(moves[0].promotion.unwrap().get_kind(), PieceKind::Queen);
+        assert!(moves[..4].iter().all(|m| m.promotion.is_some()));
+        let captures: Vec<String> = moves[4..7].iter().map(|m| m.to_uci_notation()).collect();
+        assert_eq!(captures, ["d4c5", "a3c5", "d4e5"]);
+        assert!(moves[7..].iter().all(|m| m.capture.is_none()));
+
+        let quiet = moves[10];
+        order_moves(&mut moves, Some(quiet));
+        assert_eq!(moves[0], quiet);
+    }
+
+    #[test]
+    fn test_move_picker_stages() {
+        let board = Board::from_fen("4k3/8/2p5/3n4/4P3/8/3Q3P/4K3 w - - 0 1");
+        let moves = board.get_legal_moves();
+        let find = |notation: &str| Move::from_uci_notation(&board, notation).unwrap();
+        let picked = |heuristics: &OrderingHeuristics, first, previous_move| -> Vec<String> {
+            MovePicker::new(&board, moves.clone(), first, heuristics, 3, previous_move)
+                .map(|m| m.to_uci_notation())
+                .collect()
+        };
+
+        let mut heuristics = OrderingHeuristics::new();
+        let order = picked(&heuristics, None, None);
+        assert_eq!(order.len(), moves.len());
+        // the pawn wins the knight, the queen taking it can be lost for it
+        assert_eq!(order[0], "e4d5");
+        assert_eq!(order.last().unwrap(), "d2d5");
+
+        let previous_move = Move::from_uci_notation(
+            &Board::from_fen("4k3/2p5/8/3n4/4P3/8/3Q3P/4K3 b - - 0 1"),
+            "c7c6",
+        );
+        heuristics.update(3, 4, &find("h2h4"), &[find("e1f1")], None);
+        heuristics.update(5, 4, &find("d2g5"), &[], previous_move);
+        let order = picked(&heuristics, Some(find("h2h3")), previous_move);
+        assert_eq!(order[..4], ["h2h3", "e4d5", "h2h4", "d2g5"]);
+        // quiet moves that failed to cut off come after the others
+        assert_eq!(order[order.len() - 2..], ["e1f1", "d2d5"]);
+
+        heuristics.clear();
+        assert_eq!(
+            picked(&heuristics, None, previous_move),
+            picked(&OrderingHeuristics::new(), None, None)
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{Board, r#move::Move};
+
+/// Triangular principal variation table: the line at `ply` is the best line found from the node
+/// currently searched at that ply, built from its best move and the line of the child at
+/// `ply + 1`.
+pub struct PvTable {
+    lines: Vec<Vec<Move>>,
+}
+
+impl PvTable {
+    pub fn new() -> Self {
+        Self { lines: Vec::new() }
+    }
+
+    /// Forgets the line of the node at `ply`, to be called when the node is entered.
+    pub fn clear(&mut self, ply: u8) {
+        let ply = ply as usize;
+        if self.lines.len() < ply + 2 {
+            self.lines.resize_with(ply + 2, Vec::new);
+        }
+        self.lines[ply].clear();
+    }
+
+    /// Sets the line at `ply` to `m` followed by the line of the child node.
+    pub fn update(&mut self, ply: u8, m: Move) {
+        let (head, tail) = self.lines.split_at_mut(ply as usize + 1);
+        let line = &mut head[ply as usize];
+        line.clear();
+        line.push(m);
+        line.extend_from_slice(&tail[0]);
+    }
+
+    /// Best line from the root.
+    pub fn get_pv(&self) -> &[Move] {
+        self.get_line(0)
+    }
+
+    /// Best line from the node last searched at `ply`.
+    pub fn get_line(&self, ply: u8) -> &[Move] {
+        self.lines
+            .get(ply as usize)
+            .map_or(&[], |line| line.as_slice())
+    }
+}
+
+impl Default for PvTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether `line` can be played move by move from `board`.
+pub fn is_legal_line(board: &Board, line: &[Move]) -> bool {
+    let mut board = board.clone();
+    for m in line {
+        if !board.get_legal_moves().contains(m) {
+            return false;
+        }
+        board.apply_move(m);
+    }
+    true
+}
@@ -0,0 +1,174 @@
+use super::{
+    MATE_SCORE,
+    ordering::order_moves,
+    score::{clamp_eval, mated_in},
+};
+use crate::{
+    Board,
+    board::piece::{PieceKind, PieceKind::Pawn},
+    evaluate::Evaluator,
+    r#move::Move,
+};
+
+/// Delta pruning margin, in pawns, on top of the material a move wins.
+const DELTA_MARGIN: i32 = 2;
+
+/// Searches captures and queen promotions from the leaves of a depth-limited search until the
+/// position is quiet, so that the static evaluation isn't taken in the middle of an exchange.
+/// When in check all evasions are searched instead.
+pub struct QuiescenceSearch {
+    /// Nodes visited below the position the search was started from.
+    pub nodes_searched: usize,
+    /// Deepest ply reached, counted from the root of the calling search.
+    pub max_ply: u8,
+}
+
+impl QuiescenceSearch {
+    pub fn new() -> Self {
+        Self {
+            nodes_searched: 0,
+            max_ply: 0,
+        }
+    }
+
+    /// Fail-soft score of the position for the side to move, `ply` being its distance from the
+    /// root of the calling search.
+    pub fn search(
+        &mut self,
+        board: &mut Board,
+        evaluator: &dyn Evaluator,
+        mut alpha: i32,
+        beta: i32,
+        ply: u8,
+    ) -> i32 {
+        self.max_ply = self.max_ply.max(ply);
+        let in_check = board.is_in_check();
+        let mut moves = board.get_legal_moves();
+        if moves.is_empty() && in_check {
+            return mated_in(ply);
+        }
+        if ply == u8::MAX {
+            return clamp_eval(evaluator.evaluate(board));
+        }
+
+        let mut best_score = -MATE_SCORE;
+        let mut stand_pat = None;
+        if !in_check {
+            // The side to move can usually do at least as well as the static evaluation by
+            // playing a quiet move, so it is a lower bound of the score
+            let score = clamp_eval(evaluator.evaluate(board));
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+            best_score = score;
+            stand_pat = Some(score);
+            // Captures losing material by static exchange evaluation are left out as well, the
+            // stand pat score being better than what they lead to
+            moves.retain(|m| {
+                (m.capture.is_some()
+                    || m.promotion
+                        .is_some_and(|p| p.get_kind() == PieceKind::Queen))
+                    && board.see_ge(m, 0)
+            });
+        }
+        order_moves(&mut moves, None);
+
+        let delta_margin = DELTA_MARGIN * evaluator.piece_value(Pawn);
+        for mv in &moves {
+            board.apply_move(mv);
+            // Delta pruning: skip captures that can't raise the score to alpha even with a margin,
+            // unless they give check and might lead to mate. The optimistic score still bounds
+            // the result, as the move wasn't proven to be worse.
+            if let Some(stand_pat) = stand_pat
+                && delta_margin > 0
+                && !board.is_in_check()
+            {
+                let optimistic_score =
+                    stand_pat + Self::material_gain(evaluator, mv) + delta_margin;
+                if optimistic_score <= alpha {
+                    best_score = best_score.max(optimistic_score);
+                    board.undo_move(mv);
+                    continue;
+                }
+            }
+
+            self.nodes_searched += 1;
+            let score = -self.search(board, evaluator, -beta, -alpha, ply + 1);
+            board.undo_move(mv);
+
+            if score > best_score {
+                best_score = score;
+                if score > alpha {
+                    alpha = score;
+                    if score >= beta {
+                        break;
+                    }
+                }
+            }
+        }
+
+        best_score
+    }
+
+    fn material_gain(evaluator: &dyn Evaluator, m: &Move) -> i32 {
+        let capture = m
+            .capture
+            .map_or(0, |piece| evaluator.piece_value(piece.get_kind()));
+        let promotion = m.promotion.map_or(0, |piece| {
+            evaluator.piece_value(piece.get_kind()) - evaluator.piece_value(Pawn)
+        });
+        capture + promotion
+    }
+}
+
+impl Default for QuiescenceSearch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluate::{MaterialEvaluator, TaperedScore};
+
+    fn quiescence_score(fen: &str) -> i32 {
+        let mut board = Board::from_fen(fen);
+        QuiescenceSearch::new().search(
+            &mut board,
+            &MaterialEvaluator::new(1),
+            -MATE_SCORE,
+            MATE_SCORE,
+            0,
+        )
+    }
+
+    #[test]
+    fn test_resolves_exchanges() {
+        // the knight is defended, taking it loses the queen for a knight, so the queen against
+        // knight and pawn stands, at a game phase of 5
+        assert_eq!(
+            quiescence_score("4k3/8/2p5/3n4/8/8/3Q4/4K3 w - - 0 1"),
+            TaperedScore::new(480, 520).taper(5)
+        );
+        // an undefended knight is just won
+        assert_eq!(
+            quiescence_score("4k3/8/8/3n4/8/8/3Q4/4K3 w - - 0 1"),
+            TaperedScore::new(900, 940).taper(4)
+        );
+    }
+
+    #[test]
+    fn test_detects_mate_when_in_check() {
+        assert_eq!(
+            quiescence_score("r6k/1r6/8/8/8/8/8/K7 w - - 0 1"),
+            -MATE_SCORE
+        );
+        // with a way out it is just a rook down
+        assert_eq!(
+            quiescence_score("r6k/8/8/8/8/8/8/K7 w - - 0 1"),
+            -TaperedScore::new(500, 540).taper(2)
+        );
+    }
+}
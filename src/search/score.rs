@@ -0,0 +1,109 @@
+use std::fmt::{Display, Formatter};
+
+/// Score of being checkmated at the root. A side getting mated `ply` plies from the root scores
+/// `-MATE_SCORE + ply`, so that faster mates are preferred and slower ones delayed.
+pub const MATE_SCORE: i32 = 100000;
+/// Deepest ply a search can reach, so mate scores are those within this of [`MATE_SCORE`].
+pub const MAX_PLY: i32 = u8::MAX as i32;
+
+/// Score of the side to move getting checkmated `ply` plies from the root.
+pub fn mated_in(ply: u8) -> i32 {
+    -MATE_SCORE + ply as i32
+}
+
+pub fn is_mate_score(value: i32) -> bool {
+    value.abs() >= MATE_SCORE - MAX_PLY
+}
+
+/// Largest static evaluation the search works with, well below the mate scores.
+pub const MAX_EVAL: i32 = MATE_SCORE / 2;
+
+/// Static evaluation `value` brought within [`MAX_EVAL`], so that heavily weighted evaluators
+/// can't produce scores taken for mates.
+pub fn clamp_eval(value: i32) -> i32 {
+    value.clamp(-MAX_EVAL, MAX_EVAL)
+}
+
+/// Search score from the point of view of the side to move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    Centipawns(i32),
+    /// Mate in the given number of moves, negative when the side to move gets mated.
+    Mate(i32),
+}
+
+impl Score {
+    pub fn from_value(value: i32) -> Self {
+        if !is_mate_score(value) {
+            Score::Centipawns(value)
+        } else if value > 0 {
+            Score::Mate((MATE_SCORE - value + 1) / 2)
+        } else {
+            Score::Mate(-(MATE_SCORE + value) / 2)
+        }
+    }
+
+    /// The score in centipawns rather than in the units of the evaluator, in which a pawn is worth
+    /// `pawn_value`. Mate scores are kept, and so are scores of evaluators that don't value pawns.
+    pub fn to_centipawns(self, pawn_value: i32) -> Self {
+        match self {
+            Score::Centipawns(value) if pawn_value > 0 => {
+                Score::Centipawns(value * 100 / pawn_value)
+            }
+            score => score,
+        }
+    }
+}
+
+/// Formats the score the way UCI `info` lines expect it: `cp <x>` or `mate <n>`.
+impl Display for Score {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Score::Centipawns(value) => write!(f, "cp {}", value),
+            Score::Mate(moves) => write!(f, "mate {}", moves),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_centipawns() {
+        assert_eq!(
+            Score::Centipawns(450).to_centipawns(200),
+            Score::Centipawns(225)
+        );
+        assert_eq!(
+            Score::Centipawns(-2000).to_centipawns(1000),
+            Score::Centipawns(-200)
+        );
+        assert_eq!(
+            Score::Centipawns(35).to_centipawns(0),
+            Score::Centipawns(35)
+        );
+        assert_eq!(Score::Mate(3).to_centipawns(200), Score::Mate(3));
+    }
+
+    #[test]
+    fn test_clamp_eval() {
+        assert_eq!(clamp_eval(-1200), -1200);
+        assert_eq!(clamp_eval(MATE_SCORE - 10), MAX_EVAL);
+        assert!(!is_mate_score(clamp_eval(-MATE_SCORE)));
+    }
+
+    #[test]
+    fn test_from_value() {
+        assert_eq!(Score::from_value(35), Score::Centipawns(35));
+        assert_eq!(Score::from_value(-1200), Score::Centipawns(-1200));
+        // mating move at ply 0, mate on the board at ply 1
+        assert_eq!(Score::from_value(-mated_in(1)), Score::Mate(1));
+        assert_eq!(Score::from_value(-mated_in(3)), Score::Mate(2));
+        // checkmated on the board at the root, or after one move each
+        assert_eq!(Score::from_value(mated_in(0)), Score::Mate(0));
+        assert_eq!(Score::from_value(mated_in(2)), Score::Mate(-1));
+        assert_eq!(Score::from_value(-mated_in(5)).to_string(), "mate 3");
+        assert_eq!(Score::from_value(mated_in(4)).to_string(), "mate -2");
+    }
+}
@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use super::SearchLimits;
+use crate::r#move::Move;
+
+/// Moves the remaining time is divided into when the GUI doesn't say how many are left.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+/// How many times the soft deadline the hard deadline allows for an iteration to finish.
+const HARD_TIME_FACTOR: u32 = 4;
+/// Percentage of the soft deadline after which no new iteration is started, by the number of
+/// iterations the best move has stayed the same.
+const STABILITY_TIME_PERCENT: [u32; 5] = [100, 80, 65, 55, 50];
+
+/// Clock of the side to move, as sent with `go wtime ... btime ... winc ... binc ... movestogo`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeControl {
+    pub time_left: Duration,
+    pub increment: Duration,
+    pub moves_to_go: Option<u32>,
+}
+
+impl TimeControl {
+    /// Soft and hard deadlines of the search, keeping `move_overhead` for communication with
+    /// the GUI. No new iteration is started after the soft one, the search is aborted at the
+    /// hard one.
+    pub fn allocate(&self, move_overhead: Duration) -> (Duration, Duration) {
+        let available = self.time_left.saturating_sub(move_overhead);
+        let moves_to_go = self.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+        // Never plan on using the whole clock, even on the last move before the time control
+        let max_time = available * 4 / 5;
+
+        let soft = available / moves_to_go + self.increment * 3 / 4;
+        let hard = (soft * HARD_TIME_FACTOR).min(max_time);
+        (soft.min(hard), hard)
+    }
+
+    /// Search limits for this clock, without a depth limit.
+    pub fn get_limits(&self, move_overhead: Duration) -> SearchLimits {
+        let (soft, hard) = self.allocate(move_overhead);
+        SearchLimits {
+            max_time: Some(hard),
+            soft_time: Some(soft),
+            ..Default::default()
+        }
+    }
+}
+
+/// Decides after each iteration of iterative deepening whether to start another one, spending
+/// less of the soft time limit the longer the best move stays the same.
+pub struct TimeManager {
+    soft_time: Option<Duration>,
+    best_move: Option<Move>,
+    stability: usize,
+}
+
+impl TimeManager {
+    pub fn new(limits: &SearchLimits) -> Self {
+        Self {
+            soft_time: limits.soft_time,
+            best_move: None,
+            stability: 0,
+        }
+    }
+
+    /// Records the best move of a completed iteration and returns whether the search should
+    /// stop, `elapsed` being the time since the clock started, see
+    /// [`SearchLimits::get_clock_elapsed`].
+    pub fn iteration_done(&mut self, best_move: Option<Move>, elapsed: Option<Duration>) -> bool {
+        if best_move.is_some() && best_move == self.best_move {
+            self.stability = (self.stability + 1).min(STABILITY_TIME_PERCENT.len() - 1);
+        } else {
+            self.stability = 0;
+        }
+        self.best_move = best_move;
+
+        let soft_time = self
+            .soft_time
+            .map(|soft_time| soft_time * STABILITY_TIME_PERCENT[self.stability] / 100);
+        soft_time.is_some_and(|soft_time| elapsed.is_some_and(|elapsed| elapsed >= soft_time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Board;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_allocate() {
+        let clock = TimeControl {
+            time_left: ms(60_000),
+            ..Default::default()
+        };
+        let (soft, hard) = clock.allocate(ms(0));
+        assert_eq!(soft, ms(2000));
+        assert_eq!(hard, ms(8000));
+
+        // the increment is mostly spent on the move
+        let (soft, _) = TimeControl {
+            increment: ms(1000),
+            ..clock
+        }
+        .allocate(ms(0));
+        assert_eq!(soft, ms(2750));
+
+        // last move before the time control, with a move overhead
+        let (soft, hard) = TimeControl {
+            moves_to_go: Some(1),
+            ..clock
+        }
+        .allocate(ms(1000));
+        assert_eq!(soft, ms(47_200));
+        assert_eq!(hard, ms(47_200));
+
+        // less time left than the overhead
+        let (soft, hard) = TimeControl {
+            time_left: ms(10),
+            ..clock
+        }
+        .allocate(ms(50));
+        assert_eq!((soft, hard), (ms(0), ms(0)));
+    }
+
+    #[test]
+    fn test_stops_earlier_when_best_move_is_stable() {
+        let limits = SearchLimits {
+            soft_time: Some(ms(1000)),
+            ..Default::default()
+        };
+        let board = Board::default();
+        let moves = board.get_legal_moves();
+        let mut manager = TimeManager::new(&limits);
+        assert!(!manager.iteration_done(Some(moves[0]), Some(ms(700))));
+        assert!(manager.iteration_done(Some(moves[0]), Some(ms(800))));
+
+        // a new best move resets the stability
+        assert!(!manager.iteration_done(Some(moves[1]), Some(ms(900))));
+        assert!(manager.iteration_done(Some(moves[1]), Some(ms(1000))));
+
+        // the clock doesn't run while pondering
+        assert!(!manager.iteration_done(Some(moves[1]), None));
+
+        // without a soft limit only the hard limits stop the search
+        let mut manager = TimeManager::new(&SearchLimits::default());
+        assert!(!manager.iteration_done(Some(moves[0]), Some(ms(100_000))));
+    }
+}
@@ -0,0 +1,295 @@
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+
+use super::score::is_mate_score;
+use crate::r#move::Move;
+
+pub const DEFAULT_HASH_SIZE_MB: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    /// The score is exact, it was inside the search window.
+    Exact,
+    /// The search failed high, the real score is at least this.
+    Lower,
+    /// The search failed low, the real score is at most this.
+    Upper,
+}
+
+/// Move reduced to its squares and promotion, which tells it apart from the other legal moves of
+/// its position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PackedMove(u16);
+
+impl PackedMove {
+    pub fn new(m: &Move) -> Self {
+        let promotion = m
+            .promotion
+            .map_or(0, |piece| piece.get_kind().index() as u16 + 1);
+        Self(m.from.to_index() as u16 | (m.to.to_index() as u16) << 6 | promotion << 12)
+    }
+
+    /// The move of `moves` this was packed from, if any.
+    pub fn find_in(&self, moves: &[Move]) -> Option<Move> {
+        moves.iter().find(|m| Self::new(m) == *self).copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TTEntry {
+    pub depth: u8,
+    pub bound: Bound,
+    score: i32,
+    pub best_move: Option<PackedMove>,
+    generation: u8,
+}
+
+/// The generation shares a byte with the bound, so it wraps around at 64.
+const GENERATION_MASK: u8 = 0x3f;
+
+impl TTEntry {
+    /// Score relative to the probing node. Mate scores are stored relative to the node they
+    /// were found in, so that they stay valid when the position is reached at another ply.
+    pub fn get_score(&self, ply: u8) -> i32 {
+        if !is_mate_score(self.score) {
+            self.score
+        } else if self.score > 0 {
+            self.score - ply as i32
+        } else {
+            self.score + ply as i32
+        }
+    }
+
+    /// Packs the entry in 64 bits: score, best move, depth, then bound and generation. The bound
+    /// is stored from 1, so that an entry is never 0 like an empty slot.
+    fn pack(&self) -> u64 {
+        let best_move = self.best_move.map_or(0, |packed| packed.0);
+        let bound = self.bound as u8 + 1;
+        self.score as u32 as u64
+            | (best_move as u64) << 32
+            | (self.depth as u64) << 48
+            | ((bound << 6 | self.generation) as u64) << 56
+    }
+
+    fn unpack(data: u64) -> Self {
+        let best_move = (data >> 32) as u16;
+        let bound_and_generation = (data >> 56) as u8;
+        Self {
+            depth: (data >> 48) as u8,
+            bound: match bound_and_generation >> 6 {
+                1 => Bound::Exact,
+                2 => Bound::Lower,
+                _ => Bound::Upper,
+            },
+            score: data as u32 as i32,
+            // No move goes from a1 to a1
+            best_move: (best_move != 0).then_some(PackedMove(best_move)),
+            generation: bound_and_generation & GENERATION_MASK,
+        }
+    }
+}
+
+/// Slot of the table. The key is stored xored with the data, so that a slot torn by two threads
+/// writing it at once reads back as a miss rather than as another position's entry.
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+impl Slot {
+    fn new() -> Self {
+        Self {
+            key: AtomicU64::new(0),
+            data: AtomicU64::new(0),
+        }
+    }
+
+    /// The key and entry in the slot, if not empty.
+    fn load(&self) -> Option<(u64, TTEntry)> {
+        let data = self.data.load(Ordering::Relaxed);
+        let key = self.key.load(Ordering::Relaxed) ^ data;
+        (data != 0).then(|| (key, TTEntry::unpack(data)))
+    }
+
+    fn save(&self, key: u64, entry: &TTEntry) {
+        let data = entry.pack();
+        self.key.store(key ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+
+    fn reset(&self) {
+        self.key.store(0, Ordering::Relaxed);
+        self.data.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Fixed-size hash table of search results, indexed by the Zobrist key of the position. It is
+/// lock-free, so that the threads of a parallel search can share it.
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    generation: AtomicU8,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> Self {
+        let len = (size_mb * 1024 * 1024 / size_of::<Slot>()).max(1);
+        Self {
+            slots: (0..len).map(|_| Slot::new()).collect(),
+            generation: AtomicU8::new(0),
+        }
+    }
+
+    fn index(&self, key: u64) -> usize {
+        (key % self.slots.len() as u64) as usize
+    }
+
+    fn get_generation(&self) -> u8 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    pub fn clear(&self) {
+        self.slots.iter().for_each(Slot::reset);
+        self.generation.store(0, Ordering::Relaxed);
+    }
+
+    /// Marks the start of a new search. Entries from earlier searches stay usable, but are the
+    /// first to be replaced.
+    pub fn new_search(&self) {
+        let generation = (self.get_generation() + 1) & GENERATION_MASK;
+        self.generation.store(generation, Ordering::Relaxed);
+    }
+
+    pub fn probe(&self, key: u64) -> Option<TTEntry> {
+        self.slots[self.index(key)]
+            .load()
+            .filter(|(entry_key, _)| *entry_key == key)
+            .map(|(_, entry)| entry)
+    }
+
+    /// Stores a search result, unless the slot holds a deeper result for another position from
+    /// the current search. `score` is relative to the node at `ply`.
+    pub fn store(
+        &self,
+        key: u64,
+        depth: u8,
+        bound: Bound,
+        score: i32,
+        best_move: Option<Move>,
+        ply: u8,
+    ) {
+        let slot = &self.slots[self.index(key)];
+        let generation = self.get_generation();
+        let existing = slot.load();
+        if let Some((existing_key, existing)) = existing
+            && existing_key != key
+            && existing.generation == generation
+            && existing.depth > depth
+        {
+            return;
+        }
+
+        let score = if !is_mate_score(score) {
+            score
+        } else if score > 0 {
+            score + ply as i32
+        } else {
+            score - ply as i32
+        };
+        // keep the previous best move if this search didn't find one
+        let best_move = best_move.map(|m| PackedMove::new(&m)).or_else(|| {
+            existing
+                .filter(|(existing_key, _)| *existing_key == key)
+                .and_then(|(_, existing)| existing.best_move)
+        });
+
+        slot.save(
+            key,
+            &TTEntry {
+                depth,
+                bound,
+                score,
+                best_move,
+                generation,
+            },
+        );
+    }
+
+    /// Permille of the table filled with entries from the current search, sampled on the first
+    /// thousand slots.
+    pub fn hashfull(&self) -> u16 {
+        let sample = self.slots.len().min(1000);
+        let generation = self.get_generation();
+        let used = self.slots[..sample]
+            .iter()
+            .filter_map(Slot::load)
+            .filter(|(_, entry)| entry.generation == generation)
+            .count();
+        (used * 1000 / sample) as u16
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_HASH_SIZE_MB)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Board;
+    use crate::search::MATE_SCORE;
+
+    #[test]
+    fn test_store_and_probe() {
+        let tt = TranspositionTable::new(1);
+        let len = tt.slots.len() as u64;
+        tt.store(42, 3, Bound::Exact, 25, None, 0);
+        assert_eq!(tt.probe(42).unwrap().get_score(0), 25);
+        assert!(tt.probe(42 + len).is_none());
+
+        // a shallower entry of another position doesn't replace a deeper one
+        tt.store(42 + len, 2, Bound::Lower, 10, None, 0);
+        assert!(tt.probe(42).is_some());
+
+        // unless it is from an older search
+        tt.new_search();
+        tt.store(42 + len, 2, Bound::Lower, 10, None, 0);
+        assert!(tt.probe(42).is_none());
+        assert_eq!(tt.probe(42 + len).unwrap().bound, Bound::Lower);
+
+        tt.clear();
+        assert!(tt.probe(42 + len).is_none());
+        assert_eq!(tt.hashfull(), 0);
+    }
+
+    #[test]
+    fn test_mate_scores_are_relative_to_node() {
+        let tt = TranspositionTable::new(1);
+        // mated 5 plies from the root, found at ply 3
+        tt.store(7, 4, Bound::Exact, -MATE_SCORE + 5, None, 3);
+        // the same position reached at ply 1 is mated 3 plies from the root
+        assert_eq!(tt.probe(7).unwrap().get_score(1), -MATE_SCORE + 3);
+    }
+
+    #[test]
+    fn test_packed_entries() {
+        // promotions, captures and castling
+        let board = Board::from_fen("r3k3/1P6/8/8/8/8/8/4K2R w K - 0 1");
+        let moves = board.get_legal_moves();
+        let tt = TranspositionTable::new(1);
+        for (key, m) in (1..).zip(&moves) {
+            tt.store(key, 200, Bound::Upper, -1234, Some(*m), 0);
+            let entry = tt.probe(key).unwrap();
+            assert_eq!((entry.depth, entry.bound), (200, Bound::Upper));
+            assert_eq!(entry.get_score(0), -1234);
+            assert_eq!(entry.best_move.unwrap().find_in(&moves), Some(*m));
+        }
+
+        // the best move is kept when a new search of the position doesn't find one
+        tt.store(1, 201, Bound::Lower, 5, None, 0);
+        let entry = tt.probe(1).unwrap();
+        assert_eq!(entry.bound, Bound::Lower);
+        assert_eq!(entry.best_move.unwrap().find_in(&moves), Some(moves[0]));
+    }
+}